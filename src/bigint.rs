@@ -0,0 +1,520 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Div, Mul, Rem, Shl, Shr, Sub},
+};
+
+use crate::zeroize::zeroize_limbs;
+
+/// arbitrary precision unsigned integer
+/// limbs are stored little endian (least significant limb first) and kept normalized,
+/// so there are never trailing zero limbs and zero is the empty vector
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { limbs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        Self::from_u64(1)
+    }
+
+    pub fn from_u64(value: u64) -> Self {
+        Self::from_limbs(vec![value])
+    }
+
+    /// build a number from little endian limbs, trailing zero limbs are stripped
+    pub fn from_limbs(limbs: Vec<u64>) -> Self {
+        let mut res = Self { limbs };
+        res.normalize();
+        res
+    }
+
+    /// the little endian limbs of the number, empty for zero
+    pub fn limbs(&self) -> &[u64] {
+        &self.limbs
+    }
+
+    /// parse a big endian byte string, leading zero bytes are allowed
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        let limbs = bytes
+            .rchunks(8)
+            .map(|chunk| chunk.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+            .collect();
+        Self::from_limbs(limbs)
+    }
+
+    /// minimal big endian byte representation, zero encodes as an empty vector
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.limbs.iter().rev().flat_map(|l| l.to_be_bytes()).collect();
+        let leading = bytes.iter().take_while(|b| **b == 0).count();
+        bytes.drain(..leading);
+        bytes
+    }
+
+    /// parse a hex string, whitespace is ignored so RFC style constants can be pasted as is
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits: Vec<u8> = hex
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<_>>()?;
+        if digits.is_empty() {
+            return None;
+        }
+        let limbs = digits
+            .rchunks(16)
+            .map(|chunk| chunk.iter().fold(0u64, |acc, d| (acc << 4) | *d as u64))
+            .collect();
+        Some(Self::from_limbs(limbs))
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.len() {
+            0 => Some(0),
+            1 => Some(self.limbs[0]),
+            _ => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.limbs == [1]
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.limbs.first().is_none_or(|l| l & 1 == 0)
+    }
+
+    pub fn is_odd(&self) -> bool {
+        !self.is_even()
+    }
+
+    /// number of significant bits, zero has no bits
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * 64 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// value of the bit at position `index`, counting from the least significant bit
+    pub fn bit(&self, index: usize) -> bool {
+        self.limbs
+            .get(index / 64)
+            .is_some_and(|l| (l >> (index % 64)) & 1 == 1)
+    }
+
+    /// number of trailing zero bits, zero has none
+    pub fn trailing_zeros(&self) -> usize {
+        match self.limbs.iter().position(|l| *l != 0) {
+            Some(i) => i * 64 + self.limbs[i].trailing_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// subtraction that returns None instead of panicking when `other` is larger
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if *self < *other {
+            return None;
+        }
+        let mut limbs = self.limbs.clone();
+        let mut borrow = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            if i >= other.limbs.len() && !borrow {
+                break;
+            }
+            let rhs = other.limbs.get(i).copied().unwrap_or(0);
+            let (d, b1) = limb.overflowing_sub(rhs);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+            *limb = d;
+            borrow = b1 || b2;
+        }
+        Some(Self::from_limbs(limbs))
+    }
+
+    /// raise to an arbitrary exponent without any modular reduction
+    pub fn pow(&self, exp: &Self) -> Self {
+        let mut res = Self::one();
+        for i in (0..exp.bits()).rev() {
+            res = &res * &res;
+            if exp.bit(i) {
+                res = &res * self;
+            }
+        }
+        res
+    }
+
+    /// greatest common divisor, gcd(0, 0) is zero
+    pub fn gcd(&self, other: &Self) -> Self {
+        let mut a = self.clone();
+        let mut b = other.clone();
+        while !b.is_zero() {
+            let r = &a % &b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// integer square root, the largest r with r*r <= self
+    pub fn sqrt(&self) -> Self {
+        if self.is_zero() {
+            return Self::zero();
+        }
+        // Newton iteration from a power of two above the root, decreasing monotonically
+        let mut x = &Self::one() << self.bits().div_ceil(2);
+        loop {
+            let y = &(&x + &(self / &x)) >> 1;
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+
+    /// quotient and remainder of the division by `divisor`
+    /// panics when dividing by zero, like the primitive integer types
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        assert!(!divisor.is_zero(), "attempt to divide by zero");
+        if *self < *divisor {
+            return (Self::zero(), self.clone());
+        }
+        if divisor.limbs.len() == 1 {
+            let (q, r) = self.div_rem_u64(divisor.limbs[0]);
+            return (q, Self::from_u64(r));
+        }
+        self.div_rem_knuth(divisor)
+    }
+
+    /// division by a single limb
+    pub fn div_rem_u64(&self, divisor: u64) -> (Self, u64) {
+        assert!(divisor != 0, "attempt to divide by zero");
+        let mut quotient = vec![0u64; self.limbs.len()];
+        let mut rem: u128 = 0;
+        for i in (0..self.limbs.len()).rev() {
+            let cur = (rem << 64) | self.limbs[i] as u128;
+            quotient[i] = (cur / divisor as u128) as u64;
+            rem = cur % divisor as u128;
+        }
+        (Self::from_limbs(quotient), rem as u64)
+    }
+
+    /// Knuth's algorithm D (TAOCP vol 2, 4.3.1) for divisors of two or more limbs
+    fn div_rem_knuth(&self, divisor: &Self) -> (Self, Self) {
+        let shift = divisor.limbs.last().unwrap().leading_zeros() as usize;
+        let v = (divisor << shift).limbs;
+        let mut u = (self << shift).limbs;
+        u.resize(self.limbs.len() + 1, 0);
+
+        let n = v.len();
+        let m = u.len() - n;
+        let v_top = v[n - 1] as u128;
+        let v_next = v[n - 2] as u128;
+        let mut quotient = vec![0u64; m];
+
+        for j in (0..m).rev() {
+            let num = ((u[j + n] as u128) << 64) | u[j + n - 1] as u128;
+            let mut qhat = num / v_top;
+            let mut rhat = num % v_top;
+            while qhat > u64::MAX as u128 || qhat * v_next > ((rhat << 64) | u[j + n - 2] as u128) {
+                qhat -= 1;
+                rhat += v_top;
+                if rhat > u64::MAX as u128 {
+                    break;
+                }
+            }
+
+            // multiply and subtract qhat * v from the current window of u
+            let mut carry: u128 = 0;
+            let mut borrow = false;
+            for i in 0..n {
+                let p = qhat * v[i] as u128 + carry;
+                carry = p >> 64;
+                let (d, b1) = u[i + j].overflowing_sub(p as u64);
+                let (d, b2) = d.overflowing_sub(borrow as u64);
+                u[i + j] = d;
+                borrow = b1 || b2;
+            }
+            let (d, b1) = u[j + n].overflowing_sub(carry as u64);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+            u[j + n] = d;
+
+            // qhat was one too large, add the divisor back
+            if b1 || b2 {
+                qhat -= 1;
+                let mut carry = false;
+                for i in 0..n {
+                    let (s, c1) = u[i + j].overflowing_add(v[i]);
+                    let (s, c2) = s.overflowing_add(carry as u64);
+                    u[i + j] = s;
+                    carry = c1 || c2;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as u64);
+            }
+            quotient[j] = qhat as u64;
+        }
+
+        u.truncate(n);
+        let rem = Self::from_limbs(u) >> shift;
+        (Self::from_limbs(quotient), rem)
+    }
+
+    /// wipe the limbs from memory, leaving zero
+    pub(crate) fn zeroize(&mut self) {
+        zeroize_limbs(&mut self.limbs);
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u32> for BigUint {
+    fn from(value: u32) -> Self {
+        Self::from_u64(value as u64)
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        Self::from_u64(value)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let (long, short) = match self.limbs.len() >= other.limbs.len() {
+            true => (self, other),
+            false => (other, self),
+        };
+        let mut limbs = long.limbs.clone();
+        let mut carry = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            if i >= short.limbs.len() && !carry {
+                break;
+            }
+            let rhs = short.limbs.get(i).copied().unwrap_or(0);
+            let (s, c1) = limb.overflowing_add(rhs);
+            let (s, c2) = s.overflowing_add(carry as u64);
+            *limb = s;
+            carry = c1 || c2;
+        }
+        if carry {
+            limbs.push(1);
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Sub for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: &BigUint) -> BigUint {
+        self.checked_sub(other).expect("attempt to subtract with overflow")
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry: u128 = 0;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let t = *a as u128 * *b as u128 + limbs[i + j] as u128 + carry;
+                limbs[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            limbs[i + other.limbs.len()] = carry as u64;
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Div for &BigUint {
+    type Output = BigUint;
+
+    fn div(self, other: &BigUint) -> BigUint {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem for &BigUint {
+    type Output = BigUint;
+
+    fn rem(self, other: &BigUint) -> BigUint {
+        self.div_rem(other).1
+    }
+}
+
+impl Shl<usize> for &BigUint {
+    type Output = BigUint;
+
+    fn shl(self, shift: usize) -> BigUint {
+        if self.is_zero() {
+            return BigUint::zero();
+        }
+        let (words, bits) = (shift / 64, shift % 64);
+        let mut limbs = vec![0u64; words];
+        match bits {
+            0 => limbs.extend_from_slice(&self.limbs),
+            _ => {
+                let mut carry = 0;
+                for limb in &self.limbs {
+                    limbs.push((limb << bits) | carry);
+                    carry = limb >> (64 - bits);
+                }
+                limbs.push(carry);
+            }
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Shr<usize> for &BigUint {
+    type Output = BigUint;
+
+    fn shr(self, shift: usize) -> BigUint {
+        let (words, bits) = (shift / 64, shift % 64);
+        if words >= self.limbs.len() {
+            return BigUint::zero();
+        }
+        let src = &self.limbs[words..];
+        let limbs = match bits {
+            0 => src.to_vec(),
+            _ => (0..src.len())
+                .map(|i| (src[i] >> bits) | src.get(i + 1).map_or(0, |next| next << (64 - bits)))
+                .collect(),
+        };
+        BigUint::from_limbs(limbs)
+    }
+}
+
+/// forwards the owned and mixed operand forms of a binary operator to the `&a op &b` impl
+macro_rules! forward_binop {
+    ($($imp:ident $method:ident),*) => {$(
+        impl $imp<BigUint> for BigUint {
+            type Output = BigUint;
+            fn $method(self, other: BigUint) -> BigUint {
+                (&self).$method(&other)
+            }
+        }
+
+        impl $imp<&BigUint> for BigUint {
+            type Output = BigUint;
+            fn $method(self, other: &BigUint) -> BigUint {
+                (&self).$method(other)
+            }
+        }
+
+        impl $imp<BigUint> for &BigUint {
+            type Output = BigUint;
+            fn $method(self, other: BigUint) -> BigUint {
+                self.$method(&other)
+            }
+        }
+    )*};
+}
+
+forward_binop!(Add add, Sub sub, Mul mul, Div div, Rem rem);
+
+impl Shl<usize> for BigUint {
+    type Output = BigUint;
+
+    fn shl(self, shift: usize) -> BigUint {
+        &self << shift
+    }
+}
+
+impl Shr<usize> for BigUint {
+    type Output = BigUint;
+
+    fn shr(self, shift: usize) -> BigUint {
+        &self >> shift
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // peel off 19 decimal digits at a time, the largest power of ten that fits a limb
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut chunks = Vec::new();
+        let mut rest = self.clone();
+        while !rest.is_zero() {
+            let (q, r) = rest.div_rem_u64(CHUNK);
+            chunks.push(r);
+            rest = q;
+        }
+        let mut digits = match chunks.pop() {
+            Some(top) => top.to_string(),
+            None => "0".to_string(),
+        };
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:019}", chunk));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+impl fmt::LowerHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut digits = match self.limbs.last() {
+            Some(top) => format!("{:x}", top),
+            None => "0".to_string(),
+        };
+        for limb in self.limbs.iter().rev().skip(1) {
+            digits.push_str(&format!("{:016x}", limb));
+        }
+        f.pad_integral(true, "0x", &digits)
+    }
+}
+
+impl fmt::UpperHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut digits = match self.limbs.last() {
+            Some(top) => format!("{:X}", top),
+            None => "0".to_string(),
+        };
+        for limb in self.limbs.iter().rev().skip(1) {
+            digits.push_str(&format!("{:016X}", limb));
+        }
+        f.pad_integral(true, "0x", &digits)
+    }
+}
+
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self)
+    }
+}
@@ -0,0 +1,118 @@
+use std::error;
+
+use crate::rng::Rng;
+
+/// "expand 32-byte k"
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+/// the ChaCha20 block function from RFC 8439 section 2.3: 64 bytes of keystream for the
+/// given 256 bit key, 32 bit block counter and 96 bit nonce
+pub fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&CONSTANTS);
+    for (i, chunk) in key.chunks_exact(4).enumerate() {
+        state[4 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    state[12] = counter;
+    for (i, chunk) in nonce.chunks_exact(4).enumerate() {
+        state[13 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    serialize(&permute(&state))
+}
+
+/// deterministic random bit generator running ChaCha20 as a keystream under a 32 byte seed
+/// the same seed always gives the same output, which makes prime generation and key
+/// sampling reproducible for test fixtures and simulations
+/// words 12 and 13 of the state form a 64 bit block counter and words 14 and 15 a 64 bit
+/// stream id, so with stream 0 the output is the RFC 8439 keystream for an all zero nonce
+#[derive(Clone)]
+pub struct ChaCha20Rng {
+    state: [u32; 16],
+    buffer: [u8; 64],
+    /// bytes of `buffer` already handed out, 64 when a new block is needed
+    used: usize,
+}
+
+impl ChaCha20Rng {
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self::with_stream(seed, 0)
+    }
+
+    /// independent generators under one seed, one per stream id
+    pub fn with_stream(seed: [u8; 32], stream: u64) -> Self {
+        let mut state = [0u32; 16];
+        state[..4].copy_from_slice(&CONSTANTS);
+        for (i, chunk) in seed.chunks_exact(4).enumerate() {
+            state[4 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+        state[14] = stream as u32;
+        state[15] = (stream >> 32) as u32;
+        Self {
+            state,
+            buffer: [0; 64],
+            used: 64,
+        }
+    }
+
+    fn refill(&mut self) {
+        self.buffer = serialize(&permute(&self.state));
+        self.used = 0;
+        let counter = ((self.state[13] as u64) << 32 | self.state[12] as u64).wrapping_add(1);
+        self.state[12] = counter as u32;
+        self.state[13] = (counter >> 32) as u32;
+    }
+}
+
+impl Rng for ChaCha20Rng {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Box<dyn error::Error>> {
+        let mut filled = 0;
+        while filled < dest.len() {
+            if self.used == self.buffer.len() {
+                self.refill();
+            }
+            let n = (dest.len() - filled).min(self.buffer.len() - self.used);
+            dest[filled..filled + n].copy_from_slice(&self.buffer[self.used..self.used + n]);
+            self.used += n;
+            filled += n;
+        }
+        Ok(())
+    }
+}
+
+/// 20 rounds (10 column and diagonal double rounds) followed by adding the input state
+fn permute(input: &[u32; 16]) -> [u32; 16] {
+    let mut x = *input;
+    for _ in 0..10 {
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 1, 5, 9, 13);
+        quarter_round(&mut x, 2, 6, 10, 14);
+        quarter_round(&mut x, 3, 7, 11, 15);
+        quarter_round(&mut x, 0, 5, 10, 15);
+        quarter_round(&mut x, 1, 6, 11, 12);
+        quarter_round(&mut x, 2, 7, 8, 13);
+        quarter_round(&mut x, 3, 4, 9, 14);
+    }
+    for (word, init) in x.iter_mut().zip(input) {
+        *word = word.wrapping_add(*init);
+    }
+    x
+}
+
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+fn serialize(state: &[u32; 16]) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
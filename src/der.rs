@@ -0,0 +1,114 @@
+use std::error;
+
+use crate::{BigUint, DHError};
+
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_BIT_STRING: u8 = 0x03;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+
+/// reads the DER subset key formats are built from: definite, minimally encoded lengths
+/// and single byte tags, with anything not in canonical form rejected
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// the tag of the next element, if any
+    pub(crate) fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// fail unless every byte has been consumed
+    pub(crate) fn finish(&self) -> Result<(), Box<dyn error::Error>> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(Box::new(DHError::InvalidDer)),
+        }
+    }
+
+    /// the contents of the next element, which must have tag `tag`
+    pub(crate) fn read(&mut self, tag: u8) -> Result<&'a [u8], Box<dyn error::Error>> {
+        let (&actual, rest) = self.data.split_first().ok_or(DHError::InvalidDer)?;
+        if actual != tag {
+            return Err(Box::new(DHError::InvalidDer));
+        }
+        let (&first, mut rest) = rest.split_first().ok_or(DHError::InvalidDer)?;
+        let len = match first {
+            0..=0x7f => first as usize,
+            // long form: the low bits count the length bytes that follow, which must not
+            // start with zero and must only be used for lengths of 128 or more
+            0x81..=0x84 => {
+                let n = (first & 0x7f) as usize;
+                if rest.len() < n || rest[0] == 0 {
+                    return Err(Box::new(DHError::InvalidDer));
+                }
+                let len = rest[..n].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+                rest = &rest[n..];
+                if len < 0x80 {
+                    return Err(Box::new(DHError::InvalidDer));
+                }
+                len
+            }
+            // 0x80 is the BER indefinite length, and longer lengths are not plausible here
+            _ => return Err(Box::new(DHError::InvalidDer)),
+        };
+        if rest.len() < len {
+            return Err(Box::new(DHError::InvalidDer));
+        }
+        let (contents, rest) = rest.split_at(len);
+        self.data = rest;
+        Ok(contents)
+    }
+
+    /// a reader over the contents of the next SEQUENCE
+    pub(crate) fn read_sequence(&mut self) -> Result<Reader<'a>, Box<dyn error::Error>> {
+        Ok(Reader::new(self.read(TAG_SEQUENCE)?))
+    }
+
+    /// the next INTEGER, which must be non-negative
+    pub(crate) fn read_integer(&mut self) -> Result<BigUint, Box<dyn error::Error>> {
+        let contents = self.read(TAG_INTEGER)?;
+        match contents {
+            [] => Err(Box::new(DHError::InvalidDer)),
+            // negative
+            [first, ..] if first & 0x80 != 0 => Err(Box::new(DHError::InvalidDer)),
+            // a leading zero byte is only allowed to clear the sign bit of the next one
+            [0, second, ..] if second & 0x80 == 0 => Err(Box::new(DHError::InvalidDer)),
+            _ => Ok(BigUint::from_bytes_be(contents)),
+        }
+    }
+}
+
+/// append one element of tag `tag` with the given contents
+pub(crate) fn write(out: &mut Vec<u8>, tag: u8, contents: &[u8]) {
+    out.push(tag);
+    let len = contents.len();
+    match len < 0x80 {
+        true => out.push(len as u8),
+        false => {
+            let bytes = len.to_be_bytes();
+            let skip = bytes.iter().take_while(|b| **b == 0).count();
+            out.push(0x80 | (bytes.len() - skip) as u8);
+            out.extend_from_slice(&bytes[skip..]);
+        }
+    }
+    out.extend_from_slice(contents);
+}
+
+/// append a non-negative INTEGER, with a zero byte in front when the top bit is set so it
+/// does not read as negative
+pub(crate) fn write_integer(out: &mut Vec<u8>, value: &BigUint) {
+    let mut bytes = value.to_bytes_be();
+    if bytes.first().is_none_or(|b| b & 0x80 != 0) {
+        bytes.insert(0, 0);
+    }
+    write(out, TAG_INTEGER, &bytes);
+}
@@ -0,0 +1,22 @@
+/// a Merkle-Damgard hash function usable inside HMAC, HKDF and the concatenation KDF
+/// pick the output size to match the security strength of the group, for example SHA-256
+/// for 2048 and 3072 bit groups and SHA-512 for MODP-8192
+pub trait Hash: Clone {
+    /// bytes per compression block, the HMAC key is padded to this length
+    const BLOCK_LEN: usize;
+    /// bytes of digest
+    const OUTPUT_LEN: usize;
+
+    fn new() -> Self;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> Vec<u8>;
+
+    /// hash a complete message
+    fn digest(data: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
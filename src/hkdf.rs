@@ -0,0 +1,71 @@
+use std::error;
+
+use crate::{hash::Hash, zeroize::zeroize_bytes, DHError};
+
+/// HMAC from RFC 2104, H((K ^ opad) || H((K ^ ipad) || data))
+/// keys longer than a block are hashed first, shorter ones are padded with zeros
+pub fn hmac<H: Hash>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut block = match key.len() > H::BLOCK_LEN {
+        true => H::digest(key),
+        false => key.to_vec(),
+    };
+    block.resize(H::BLOCK_LEN, 0);
+
+    let mut pad: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    let mut inner = H::new();
+    inner.update(&pad);
+    inner.update(data);
+    let mut inner = inner.finalize();
+
+    for (p, b) in pad.iter_mut().zip(&block) {
+        *p = b ^ 0x5c;
+    }
+    let mut outer = H::new();
+    outer.update(&pad);
+    outer.update(&inner);
+    zeroize_bytes(&mut block);
+    zeroize_bytes(&mut pad);
+    zeroize_bytes(&mut inner);
+    outer.finalize()
+}
+
+/// HKDF-Extract from RFC 5869 section 2.2, condense the input keying material into a
+/// uniformly random pseudorandom key
+/// an empty salt stands for a string of zeros as long as the hash output
+pub fn hkdf_extract<H: Hash>(salt: &[u8], ikm: &[u8]) -> Vec<u8> {
+    hmac::<H>(salt, ikm)
+}
+
+/// HKDF-Expand from RFC 5869 section 2.3, stretch a pseudorandom key into `len` bytes of
+/// output keying material bound to `info`
+/// at most 255 hash outputs can be produced
+pub fn hkdf_expand<H: Hash>(prk: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    if len > 255 * H::OUTPUT_LEN {
+        return Err(Box::new(DHError::DerivedKeyTooLong));
+    }
+    let mut okm = Vec::with_capacity(len);
+    let mut t = Vec::new();
+    let mut message = Vec::with_capacity(H::OUTPUT_LEN + info.len() + 1);
+    for i in 1..=len.div_ceil(H::OUTPUT_LEN) {
+        // T(i) = HMAC(PRK, T(i - 1) || info || i), with T(0) empty
+        message.clear();
+        message.extend_from_slice(&t);
+        message.extend_from_slice(info);
+        message.push(i as u8);
+        zeroize_bytes(&mut t);
+        t = hmac::<H>(prk, &message);
+        let n = (len - okm.len()).min(H::OUTPUT_LEN);
+        okm.extend_from_slice(&t[..n]);
+    }
+    zeroize_bytes(&mut t);
+    zeroize_bytes(&mut message);
+    Ok(okm)
+}
+
+/// extract then expand in one call
+pub fn hkdf<H: Hash>(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    let mut prk = hkdf_extract::<H>(salt, ikm);
+    let okm = hkdf_expand::<H>(&prk, info, len);
+    zeroize_bytes(&mut prk);
+    okm
+}
@@ -0,0 +1,86 @@
+use std::error;
+
+use crate::{hash::Hash, zeroize::zeroize_bytes, DHError};
+
+/// the single step key derivation function from NIST SP 800-56C section 4.1 with H(x) = hash
+/// the output is H(counter || Z || OtherInfo) for a 32 bit big endian counter starting at 1,
+/// concatenated and truncated to `len` bytes
+pub fn concat_kdf<H: Hash>(z: &[u8], other_info: &[u8], len: usize) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    let reps = len.div_ceil(H::OUTPUT_LEN);
+    if reps > u32::MAX as usize {
+        return Err(Box::new(DHError::DerivedKeyTooLong));
+    }
+    let mut okm = Vec::with_capacity(len);
+    for counter in 1..=reps as u32 {
+        let mut hasher = H::new();
+        hasher.update(&counter.to_be_bytes());
+        hasher.update(z);
+        hasher.update(other_info);
+        let mut block = hasher.finalize();
+        let n = (len - okm.len()).min(H::OUTPUT_LEN);
+        okm.extend_from_slice(&block[..n]);
+        zeroize_bytes(&mut block);
+    }
+    Ok(okm)
+}
+
+/// the FixedInfo of SP 800-56C in the concatenation format of SP 800-56A section 5.8.2.1.1,
+/// binding the derived key to the algorithm it is for and to both parties
+/// every field is written as a 32 bit big endian byte length followed by the data, so no
+/// two different sets of fields encode to the same string
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OtherInfo {
+    /// how the derived key will be used, for example an AEAD name
+    algorithm_id: Vec<u8>,
+    /// identity and any nonce of the initiator
+    party_u_info: Vec<u8>,
+    /// identity and any nonce of the responder
+    party_v_info: Vec<u8>,
+    /// further public data both parties know, appended only when set
+    supp_pub_info: Option<Vec<u8>>,
+    /// further private data both parties know, appended only when set
+    supp_priv_info: Option<Vec<u8>>,
+}
+
+impl OtherInfo {
+    pub fn new(algorithm_id: &[u8]) -> Self {
+        Self {
+            algorithm_id: algorithm_id.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_party_u_info(mut self, info: &[u8]) -> Self {
+        self.party_u_info = info.to_vec();
+        self
+    }
+
+    pub fn with_party_v_info(mut self, info: &[u8]) -> Self {
+        self.party_v_info = info.to_vec();
+        self
+    }
+
+    pub fn with_supp_pub_info(mut self, info: &[u8]) -> Self {
+        self.supp_pub_info = Some(info.to_vec());
+        self
+    }
+
+    pub fn with_supp_priv_info(mut self, info: &[u8]) -> Self {
+        self.supp_priv_info = Some(info.to_vec());
+        self
+    }
+
+    /// AlgorithmID || PartyUInfo || PartyVInfo {|| SuppPubInfo} {|| SuppPrivInfo}
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let fields = [&self.algorithm_id, &self.party_u_info, &self.party_v_info]
+            .into_iter()
+            .chain(self.supp_pub_info.as_ref())
+            .chain(self.supp_priv_info.as_ref());
+        let mut out = Vec::new();
+        for field in fields {
+            out.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            out.extend_from_slice(field);
+        }
+        out
+    }
+}
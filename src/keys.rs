@@ -0,0 +1,341 @@
+use std::{error, fmt};
+
+use crate::{
+    hash::Hash, hkdf::hkdf, kdf::{concat_kdf, OtherInfo}, prime::prime_factors, rng::random_bits, zeroize::zeroize_bytes, BigUint, DHError,
+    Parameters, Rng,
+};
+
+/// one party's secret exponent x, never leaves the party that generated it
+/// the exponent is wiped from memory on drop and never printed
+pub struct PrivateKey {
+    params: Parameters,
+    x: BigUint,
+}
+
+/// one party's public value g^x mod p, sent to the peer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicKey {
+    params: Parameters,
+    y: BigUint,
+}
+
+/// the group element both parties arrive at, g^(x_a * x_b) mod p
+/// wiped from memory on drop and never printed
+pub struct SharedSecret {
+    params: Parameters,
+    z: BigUint,
+}
+
+impl PrivateKey {
+    /// wrap a secret exponent, which must lie in [1, q - 1] when the subgroup order q is
+    /// known and in [1, p - 2] otherwise
+    /// p must be odd, as there is no constant time exponentiation for an even modulus
+    pub fn new(params: Parameters, x: BigUint) -> Result<Self, Box<dyn error::Error>> {
+        params.check_secret_modulus()?;
+        if x.is_zero() || x >= Self::order(&params) {
+            return Err(Box::new(DHError::InvalidPrivateKey));
+        }
+        Ok(Self { params, x })
+    }
+
+    /// generate a fresh private key uniformly from [2, n - 2], where n is the subgroup order q
+    /// when it is known and p - 1 otherwise
+    /// parameters with a private value length generate keys as `generate_with_length` does
+    pub fn generate<R: Rng + ?Sized>(params: Parameters, rng: &mut R) -> Result<Self, Box<dyn error::Error>> {
+        if let Some(bits) = params.private_value_length() {
+            return Self::generate_with_length(params, bits, rng);
+        }
+        params.check_secret_modulus()?;
+        let upper = Self::highest_generated(&params)?;
+        let x = sample_range(&upper, rng)?;
+        Ok(Self { params, x })
+    }
+
+    /// generate a short exponent private key uniformly from [2, min(2^bits - 1, n - 2)],
+    /// for groups without a small subgroup where a full size exponent is needlessly slow
+    /// `bits` should be at least twice the security strength of the group
+    pub fn generate_with_length<R: Rng + ?Sized>(
+        params: Parameters,
+        bits: usize,
+        rng: &mut R,
+    ) -> Result<Self, Box<dyn error::Error>> {
+        if bits < 2 {
+            return Err(Box::new(DHError::InvalidBitLength));
+        }
+        params.check_secret_modulus()?;
+        let full = Self::highest_generated(&params)?;
+        // clamp before shifting so an oversized length cannot allocate a huge number
+        let upper = match bits < full.bits() {
+            true => &(&BigUint::one() << bits) - &BigUint::one(),
+            false => full,
+        };
+        let x = sample_range(&upper, rng)?;
+        Ok(Self { params, x })
+    }
+
+    /// the order the exponent is reduced by: q when known, otherwise p - 1
+    fn order(params: &Parameters) -> BigUint {
+        match params.q() {
+            Some(q) => q.clone(),
+            None => params.p() - &BigUint::one(),
+        }
+    }
+
+    /// n - 2, the top of the range keys are generated from
+    fn highest_generated(params: &Parameters) -> Result<BigUint, Box<dyn error::Error>> {
+        match Self::order(params).checked_sub(&BigUint::from_u64(2)) {
+            Some(upper) => Ok(upper),
+            None => Err(Box::new(DHError::InvalidP)),
+        }
+    }
+
+    pub fn parameters(&self) -> &Parameters {
+        &self.params
+    }
+
+    /// compute the public value g^x mod p to send to the peer
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey {
+            params: self.params.clone(),
+            y: self.params.pow_secret_mod_p(self.params.g(), &self.x),
+        }
+    }
+
+    /// compute the shared secret from the peer's public value, y^x mod p
+    /// the peer's value is validated first, see `PublicKey::validate`, which without a known
+    /// subgroup order only range checks it, see `diffie_hellman_with_min_order` for such groups
+    /// should always match the peer's result for our public key
+    pub fn diffie_hellman(&self, peer: &PublicKey) -> Result<SharedSecret, Box<dyn error::Error>> {
+        if peer.params != self.params {
+            return Err(Box::new(DHError::ParameterMismatch));
+        }
+        peer.validate()?;
+        Ok(SharedSecret {
+            params: self.params.clone(),
+            z: self.params.pow_secret_mod_p(&peer.y, &self.x),
+        })
+    }
+
+    /// `diffie_hellman` that also refuses a peer value whose order has fewer than
+    /// `min_bits` bits, see `PublicKey::is_low_order`
+    /// for groups without a known subgroup order, where `validate` cannot tell a small
+    /// subgroup element from an honest one, at the cost of factoring p - 1
+    pub fn diffie_hellman_with_min_order(
+        &self,
+        peer: &PublicKey,
+        min_bits: usize,
+    ) -> Result<SharedSecret, Box<dyn error::Error>> {
+        if peer.params != self.params {
+            return Err(Box::new(DHError::ParameterMismatch));
+        }
+        peer.validate()?;
+        if peer.is_low_order(min_bits)? {
+            return Err(Box::new(DHError::PublicKeyLowOrder));
+        }
+        Ok(SharedSecret {
+            params: self.params.clone(),
+            z: self.params.pow_secret_mod_p(&peer.y, &self.x),
+        })
+    }
+}
+
+/// uniform sample from [2, upper] by rejection: draw as many bits as upper has and retry
+/// until the draw lands in range, which takes fewer than two draws on average
+fn sample_range<R: Rng + ?Sized>(upper: &BigUint, rng: &mut R) -> Result<BigUint, Box<dyn error::Error>> {
+    let two = BigUint::from_u64(2);
+    if *upper < two {
+        return Err(Box::new(DHError::InvalidBitLength));
+    }
+    loop {
+        let mut x = random_bits(rng, upper.bits())?;
+        if x >= two && x <= *upper {
+            return Ok(x);
+        }
+        // rejected draws share the rng stream with the accepted key, wipe them as well
+        x.zeroize();
+    }
+}
+
+/// x as big endian bytes, left padded with zeros to the byte length of p
+/// the limbs are written straight into the output, as `BigUint::to_bytes_be` would leave a
+/// copy of a secret x in the capacity it drains the leading zeros from
+fn encode(params: &Parameters, x: &BigUint) -> Vec<u8> {
+    let mut padded = vec![0u8; params.p().bits().div_ceil(8)];
+    for (i, byte) in padded.iter_mut().rev().enumerate() {
+        let limb = x.limbs().get(i / 8).copied().unwrap_or(0);
+        *byte = (limb >> (8 * (i % 8))) as u8;
+    }
+    padded
+}
+
+/// the value of at most p's byte length of big endian bytes, which must be below p
+fn decode(params: &Parameters, bytes: &[u8]) -> Result<BigUint, Box<dyn error::Error>> {
+    if bytes.len() > params.p().bits().div_ceil(8) {
+        return Err(Box::new(DHError::EncodingTooLong));
+    }
+    let x = BigUint::from_bytes_be(bytes);
+    if x >= *params.p() {
+        return Err(Box::new(DHError::NotReduced));
+    }
+    Ok(x)
+}
+
+impl PublicKey {
+    /// wrap a public value received from the peer
+    pub fn new(params: Parameters, y: BigUint) -> Self {
+        Self { params, y }
+    }
+
+    pub fn parameters(&self) -> &Parameters {
+        &self.params
+    }
+
+    /// the public value y
+    pub fn value(&self) -> &BigUint {
+        &self.y
+    }
+
+    /// y as big endian bytes, left padded with zeros to the byte length of p
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        encode(&self.params, &self.y)
+    }
+
+    /// parse a public value received as big endian bytes
+    /// inputs longer than p or not reduced modulo p are rejected, shorter inputs are
+    /// accepted as if left padded, range and subgroup checks are left to `validate`
+    pub fn from_bytes_be(params: Parameters, bytes: &[u8]) -> Result<Self, Box<dyn error::Error>> {
+        let y = decode(&params, bytes)?;
+        Ok(Self { params, y })
+    }
+
+    /// full public key validation from NIST SP 800-56A section 5.6.2.3.1
+    /// y must lie in [2, p - 2], which rules out 0, 1 and p - 1 as they force a trivial shared
+    /// secret, and when the subgroup order q is known y^q = 1 mod p must hold so y really
+    /// lies in the subgroup generated by g
+    pub fn validate(&self) -> Result<(), Box<dyn error::Error>> {
+        let p = self.params.p();
+        let p_minus_one = p - &BigUint::one();
+        if self.y < BigUint::from_u64(2) || self.y >= p_minus_one {
+            return Err(Box::new(DHError::PublicKeyOutOfRange));
+        }
+        if let Some(q) = self.params.q() {
+            if !self.params.pow_mod_p(&self.y, q).is_one() {
+                return Err(Box::new(DHError::PublicKeyNotInSubgroup));
+            }
+        }
+        Ok(())
+    }
+
+    /// the multiplicative order of y mod p, the smallest k > 0 with y^k = 1 mod p
+    /// starting from p - 1, each prime factor r is divided out for as long as y^(k / r) stays 1,
+    /// so this needs p - 1 to be factorable by `prime_factors`
+    pub fn order(&self) -> Result<BigUint, Box<dyn error::Error>> {
+        let p = self.params.p();
+        let y = &self.y % p;
+        if y.is_zero() {
+            return Err(Box::new(DHError::PublicKeyOutOfRange));
+        }
+        let p_minus_one = p - &BigUint::one();
+        let mut order = p_minus_one.clone();
+        for r in prime_factors(&p_minus_one).ok_or(DHError::CannotFactorOrder)? {
+            while (&order % &r).is_zero() {
+                let reduced = &order / &r;
+                if !self.params.pow_mod_p(&y, &reduced).is_one() {
+                    break;
+                }
+                order = reduced;
+            }
+        }
+        Ok(order)
+    }
+
+    /// flag a y whose order has fewer than `min_bits` bits
+    /// a shared secret computed from such a y is confined to a subgroup small enough for the
+    /// peer to search, which tells them our private key modulo that order
+    pub fn is_low_order(&self, min_bits: usize) -> Result<bool, Box<dyn error::Error>> {
+        Ok(self.order()?.bits() < min_bits)
+    }
+}
+
+impl SharedSecret {
+    /// the raw shared group element
+    /// an escape hatch around the protections of this type: the `BigUint` prints in full
+    /// with `{:?}` and any clone or derived value is not wiped on drop, prefer `derive_key`
+    pub fn value(&self) -> &BigUint {
+        &self.z
+    }
+
+    /// z as big endian bytes, left padded with zeros to the byte length of p as RFC 2631
+    /// and TLS require, so both parties hash the same string whatever the size of z
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        encode(&self.params, &self.z)
+    }
+
+    /// restore a shared secret from its big endian encoding, with the same length and
+    /// reduction checks as `PublicKey::from_bytes_be`
+    pub fn from_bytes_be(params: Parameters, bytes: &[u8]) -> Result<Self, Box<dyn error::Error>> {
+        let z = decode(&params, bytes)?;
+        Ok(Self { params, z })
+    }
+
+    /// derive `len` bytes of key material with HKDF over the hash H, for use as symmetric keys
+    /// the raw element is not uniformly random and must never be used as a key directly
+    /// the input keying material is `to_bytes_be`, z padded to the length of p
+    pub fn derive_key<H: Hash>(&self, salt: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, Box<dyn error::Error>> {
+        let mut ikm = self.to_bytes_be();
+        let okm = hkdf::<H>(salt, &ikm, info, len);
+        zeroize_bytes(&mut ikm);
+        okm
+    }
+
+    /// derive `len` bytes of key material with the NIST SP 800-56C single step KDF over the
+    /// hash H, for integrations that require it in place of HKDF
+    /// Z is `to_bytes_be`, padded to the length of p the same way as for `derive_key`
+    pub fn derive_key_concat<H: Hash>(&self, other_info: &OtherInfo, len: usize) -> Result<Vec<u8>, Box<dyn error::Error>> {
+        let mut z = self.to_bytes_be();
+        let okm = concat_kdf::<H>(&z, &other_info.to_bytes(), len);
+        zeroize_bytes(&mut z);
+        okm
+    }
+}
+
+impl PartialEq for SharedSecret {
+    /// compares every limb so the time taken does not reveal where the secrets differ
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = (self.z.limbs(), other.z.limbs());
+        let len = a.len().max(b.len());
+        let diff = (0..len).fold(0u64, |acc, i| {
+            acc | (a.get(i).copied().unwrap_or(0) ^ b.get(i).copied().unwrap_or(0))
+        });
+        diff == 0
+    }
+}
+
+impl Eq for SharedSecret {}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        self.x.zeroize();
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.z.zeroize();
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey")
+            .field("params", &self.params)
+            .field("x", &"<redacted>")
+            .finish()
+    }
+}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedSecret").field("z", &"<redacted>").finish()
+    }
+}
@@ -1,116 +1,366 @@
-use std::{collections::HashSet, error::{self, Error}, fmt::Display};
+use std::{error::{self, Error}, fmt::Display, io};
 
-struct DiffieHellman {
+mod bigint;
+mod chacha;
+mod der;
+mod groups;
+mod hash;
+mod hkdf;
+mod kdf;
+mod keys;
+mod modular;
+mod montgomery;
+mod pem;
+mod pkcs3;
+mod prime;
+mod rng;
+mod sha256;
+mod sha512;
+mod x942;
+mod zeroize;
+
+pub use bigint::BigUint;
+pub use chacha::{chacha20_block, ChaCha20Rng};
+pub use groups::NamedGroup;
+pub use hash::Hash;
+pub use hkdf::{hkdf, hkdf_expand, hkdf_extract, hmac};
+pub use kdf::{concat_kdf, OtherInfo};
+pub use keys::{PrivateKey, PublicKey, SharedSecret};
+pub use modular::{mod_pow, mod_pow_ct, mod_pow_u64};
+pub use montgomery::MontgomeryContext;
+pub use prime::{
+    baillie_psw, generate_prime, generate_safe_prime, jacobi, miller_rabin, miller_rabin_u64, prime_factors,
+    PrimalityPolicy, DEFAULT_MILLER_RABIN_ROUNDS,
+};
+pub use rng::{OsRng, Rng};
+#[doc(hidden)]
+pub use rng::fill_from_device;
+pub use sha256::{sha256, Sha256};
+pub use sha512::{sha384, sha512, Sha384, Sha512};
+pub use x942::ValidationParams;
+
+/// the public group parameters both parties agree on before an exchange
+#[derive(Clone, Debug)]
+pub struct Parameters {
     /// large prime number
-    p: u32, 
-    /// a primitive root of P 
-    g: u32, 
-    /// the result of person A's equation
-    /// person B uses this to calculate shared secret
-    x: Option<u32>,
-    /// the result of person B's equation
-    /// person A uses this to calculate shared secret
-    y: Option<u32>
+    p: BigUint, 
+    /// a primitive root of P, or a generator of the subgroup of order q
+    g: BigUint, 
+    /// the prime order of the subgroup generated by g, when g is not a primitive root
+    q: Option<BigUint>,
+    /// length in bits of the private keys to generate, the privateValueLength of PKCS#3
+    private_value_length: Option<usize>,
+    /// the cofactor j = (p - 1) / q, when known
+    j: Option<BigUint>,
+    /// the seed and counter p and q were generated from, carried through for X9.42
+    validation_params: Option<ValidationParams>,
+    /// Montgomery precomputation for p, built once and reused by every exponentiation
+    /// None when p is even
+    mont: Option<MontgomeryContext>,
 }
 
-impl DiffieHellman {
+impl Parameters {
 
-    pub fn new(p: u32, g: u32) -> Self {
+    pub fn new(p: BigUint, g: BigUint) -> Self {
+        let mont = MontgomeryContext::new(&p);
         Self {
             p,
             g, 
-            x: None, 
-            y: None,
+            q: None,
+            private_value_length: None,
+            j: None,
+            validation_params: None,
+            mont,
+        }
+    }
+    
+    /// generate fresh parameters: a random safe prime p of `bits` bits and the smallest
+    /// primitive root of p as the generator
+    pub fn generate<R: Rng + ?Sized>(bits: usize, rng: &mut R) -> Result<Self, Box<dyn error::Error>> {
+        let p = generate_safe_prime(bits, rng)?;
+        let mut params = Parameters::new(p, BigUint::from_u64(2));
+        while params.check_primitive_root(&params.g).is_err() {
+            params.g = &params.g + &BigUint::one();
         }
+        Ok(params)
+    }
+
+    /// record that g generates a subgroup of prime order q rather than the whole group
+    pub fn with_subgroup_order(mut self, q: BigUint) -> Self {
+        self.q = Some(q);
+        self
+    }
+
+    /// generate private keys of `bits` bits rather than the full size of the group
+    pub fn with_private_value_length(mut self, bits: usize) -> Self {
+        self.private_value_length = Some(bits);
+        self
+    }
+
+    /// record the cofactor j = (p - 1) / q, checked by `is_valid` when q is also known
+    pub fn with_cofactor(mut self, j: BigUint) -> Self {
+        self.j = Some(j);
+        self
+    }
+
+    /// record the seed and counter the parameters were generated from
+    pub fn with_validation_params(mut self, validation_params: ValidationParams) -> Self {
+        self.validation_params = Some(validation_params);
+        self
+    }
+
+    /// the prime modulus
+    pub fn p(&self) -> &BigUint {
+        &self.p
+    }
+
+    /// the generator
+    pub fn g(&self) -> &BigUint {
+        &self.g
+    }
+
+    /// the order of the subgroup generated by g, if known
+    pub fn q(&self) -> Option<&BigUint> {
+        self.q.as_ref()
+    }
+
+    /// the cofactor (p - 1) / q, if known
+    pub fn j(&self) -> Option<&BigUint> {
+        self.j.as_ref()
+    }
+
+    /// the generation seed and counter, if known
+    pub fn validation_params(&self) -> Option<&ValidationParams> {
+        self.validation_params.as_ref()
+    }
+
+    /// the length in bits of generated private keys, if limited
+    pub fn private_value_length(&self) -> Option<usize> {
+        self.private_value_length
     }
     
+    /// compute base^exp mod p for a secret exponent, always in constant time
+    /// through the cached Montgomery context, which private keys require to exist
+    pub(crate) fn pow_secret_mod_p(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        match &self.mont {
+            Some(ctx) => ctx.pow_ct(base, exp),
+            None => unreachable!("private keys are only built over an odd p"),
+        }
+    }
+
+    /// compute base^exp mod p for a public exponent, in variable time through the cached
+    /// Montgomery context, or through `mod_pow` when p is even and there is none
+    pub fn pow_mod_p(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        match &self.mont {
+            Some(ctx) => ctx.pow(base, exp),
+            None => mod_pow(base, exp, &self.p),
+        }
+    }
+
+    /// secret exponents can only be handled in constant time modulo an odd p
+    pub(crate) fn check_secret_modulus(&self) -> Result<(), Box<dyn error::Error>> {
+        match self.mont.is_some() {
+            true => Ok(()),
+            false => Err(Box::new(DHError::InvalidP)),
+        }
+    }
+
+    /// cheap sanity checks on imported p and g, before anything computes with them:
+    /// p must be odd and at least 3 and g must lie in [2, p - 2]
+    /// primality and the order of g are left to `is_valid`, which is far more expensive
+    pub(crate) fn check_imported(p: &BigUint, g: &BigUint) -> Result<(), Box<dyn error::Error>> {
+        if *p < BigUint::from_u64(3) || p.is_even() {
+            return Err(Box::new(DHError::InvalidP));
+        }
+        if *g < BigUint::from_u64(2) || *g >= p - &BigUint::one() {
+            return Err(Box::new(DHError::InvalidG));
+        }
+        Ok(())
+    }
+
     /// check if p is a prime number 
-    pub fn is_prime(number: &u32) -> Result<(), Box<dyn error::Error>> {
-        match number {
-            0 => return Err(Box::new(DHError::InvalidP)),
-            1 => return Err(Box::new(DHError::InvalidP)),
-            2 => return Ok(()),
-            _ => {  
-                let mut i = 2; 
-                while i*i <= *number {
-                    if number % i == 0 {
-                        return Err(Box::new(DHError::InvalidP));
-                    }
-                    i += 1; 
-                }
-                return Ok(());
-            },
+    /// uses the default `PrimalityPolicy`, Baillie-PSW, exact below 2^64
+    pub fn is_prime(number: &BigUint) -> Result<(), Box<dyn error::Error>> {
+        Parameters::is_prime_with(number, PrimalityPolicy::default())
+    }
+
+    /// check if p is a prime number with the given number of Miller-Rabin rounds
+    pub fn is_prime_with_rounds(number: &BigUint, rounds: usize) -> Result<(), Box<dyn error::Error>> {
+        Parameters::is_prime_with(number, PrimalityPolicy::MillerRabin(rounds))
+    }
+
+    /// check if p is a prime number using the given primality test
+    pub fn is_prime_with(number: &BigUint, policy: PrimalityPolicy) -> Result<(), Box<dyn error::Error>> {
+        match policy.is_probable_prime(number) {
+            true => Ok(()),
+            false => Err(Box::new(DHError::InvalidP)),
+        }
+    }
+
+    /// check if p is a safe prime, p = 2q + 1 with q prime, and return the subgroup order q
+    pub fn is_safe_prime(number: &BigUint) -> Result<BigUint, Box<dyn error::Error>> {
+        Parameters::is_safe_prime_with(number, PrimalityPolicy::default())
+    }
+
+    /// check if p is a safe prime using the given primality test for both p and q
+    pub fn is_safe_prime_with(number: &BigUint, policy: PrimalityPolicy) -> Result<BigUint, Box<dyn error::Error>> {
+        Parameters::is_prime_with(number, policy)?;
+        let q = number >> 1;
+        match number.is_odd() && policy.is_probable_prime(&q) {
+            true => Ok(q),
+            false => Err(Box::new(DHError::NotSafePrime)),
         }
     }
 
     /// check if g is a primitive root of p 
-    pub fn is_primitive_root(prime: &u32, g: &u32) -> Result<(), Box<dyn error::Error>> {
-        // create powers of {p_root} mod {prime}
-        // Euler Phi Function
-        let mut res: HashSet<_> = HashSet::new(); 
-        for i in 1..*prime {
-            let value: u32 = i.pow(*g) % prime;
-            match res.contains(&value) {
-                false => res.insert(value),
-                true => {return Err(Box::new(DHError::InvalidG));}  
-            };    
+    /// g generates the whole group mod p exactly when g^((p-1)/q) != 1 for every prime factor q of p-1
+    pub fn is_primitive_root(prime: &BigUint, g: &BigUint) -> Result<(), Box<dyn error::Error>> {
+        Parameters::new(prime.clone(), g.clone()).check_primitive_root(g)
+    }
+
+    /// `is_primitive_root` modulo our p, reusing its Montgomery context for every factor
+    fn check_primitive_root(&self, g: &BigUint) -> Result<(), Box<dyn error::Error>> {
+        if (g % &self.p).is_zero() {
+            return Err(Box::new(DHError::InvalidG));
+        }
+        let order = &self.p - &BigUint::one();
+        let factors = prime_factors(&order).ok_or(DHError::CannotFactorOrder)?;
+        for q in factors {
+            if self.pow_mod_p(g, &(&order / &q)).is_one() {
+                return Err(Box::new(DHError::InvalidG));
+            }
+        }
+        Ok(()) 
+    }
+
+    /// check if g generates a subgroup of prime order q: q must be a prime divisor of p - 1,
+    /// and g must be neither 0 nor 1 with g^q = 1 mod p
+    pub fn is_subgroup_generator(prime: &BigUint, g: &BigUint, q: &BigUint, policy: PrimalityPolicy) -> Result<(), Box<dyn error::Error>> {
+        Parameters::new(prime.clone(), g.clone()).check_subgroup_generator(q, policy)
+    }
+
+    /// `is_subgroup_generator` for our p and g, through the cached Montgomery context
+    fn check_subgroup_generator(&self, q: &BigUint, policy: PrimalityPolicy) -> Result<(), Box<dyn error::Error>> {
+        let p_minus_one = &self.p - &BigUint::one();
+        if !policy.is_probable_prime(q) || !(&p_minus_one % q).is_zero() {
+            return Err(Box::new(DHError::InvalidQ));
         }
-        return Ok(()); 
+        let g = &self.g % &self.p;
+        if g.is_zero() || g.is_one() || !self.pow_mod_p(&g, q).is_one() {
+            return Err(Box::new(DHError::InvalidG));
+        }
+        Ok(())
     }
 
     /// ensures the valid setup to a Diffie Hellman key exchange
     /// bubbles up errors from primtive root fn and prime number fn
+    /// when the subgroup order q is known, g is checked to generate that subgroup instead
+    /// and a known cofactor j must satisfy p - 1 = j * q
     pub fn is_valid(&self) -> Result<(), Box<dyn error::Error>> {
-        DiffieHellman::is_prime(&self.p)?;
-        DiffieHellman::is_primitive_root( &self.p, &self.g)?;
+        self.is_valid_with(PrimalityPolicy::default())
+    }
+
+    /// same as `is_valid` but deciding whether p is prime with the given primality test
+    pub fn is_valid_with(&self, policy: PrimalityPolicy) -> Result<(), Box<dyn error::Error>> {
+        Parameters::is_prime_with(&self.p, policy)?;
+        match &self.q {
+            Some(q) => self.check_subgroup_generator(q, policy)?,
+            None => self.check_primitive_root(&self.g)?,
+        }
+        if let (Some(q), Some(j)) = (&self.q, &self.j) {
+            if q * j != &self.p - &BigUint::one() {
+                return Err(Box::new(DHError::InvalidCofactor));
+            }
+        }
         Ok(())
     }
 
-    /// compute the public value for person A using their own secret, outputs a number usable by person B to calculate the shared secret 
-    pub fn calculate_pub_x(mut self, secret: u32) -> Self {
-        self.x = Some(self.g.pow(secret) % self.p);
-        self
-    } 
-    
-    /// compute the public value for person B using their own secret, outputs a number usable by person A to calculate the shared secret 
-    pub fn calculate_pub_y(mut self, secret: u32) -> Self {
-        self.y = Some(self.g.pow(secret) % self.p);
-        self
-    } 
+    /// stricter version of `is_valid` that also requires p to be a safe prime and
+    /// returns the order q of the prime order subgroup
+    /// modulo a safe prime every g in [2, p - 2] has order q or 2q, so unlike `is_valid` this
+    /// also accepts generators of the order q subgroup such as g = 2 in the RFC 3526 groups
+    pub fn is_valid_safe_prime(&self) -> Result<BigUint, Box<dyn error::Error>> {
+        self.is_valid_safe_prime_with(PrimalityPolicy::default())
+    }
 
-    /// compute the shared secret for person A using the public value calculated by person B (y)
-    /// hould always match the output of shared_secret_b 
-    pub fn shared_secret_a(&self, secret: u32) -> Result<u32, Box<dyn error::Error>> {
-        match self.y {
-            Some(y) => return Ok(y.pow(secret) % self.p),
-            None => return Err(Box::new(DHError::SecretNotComputed)),
+    /// same as `is_valid_safe_prime` but deciding primality with the given primality test
+    pub fn is_valid_safe_prime_with(&self, policy: PrimalityPolicy) -> Result<BigUint, Box<dyn error::Error>> {
+        let q = Parameters::is_safe_prime_with(&self.p, policy)?;
+        let p_minus_one = &self.p - &BigUint::one();
+        if self.g < BigUint::from_u64(2) || self.g >= p_minus_one {
+            return Err(Box::new(DHError::InvalidG));
         }
+        Ok(q)
     }
-    
-    /// compute the shared secret for person B using the public value calculated by person A (x)
-    /// should always match the output of shared_secret_a 
-    pub fn shared_secret_b(&self, secret: u32) -> Result<u32, Box<dyn error::Error>> {
-        match self.x {
-            Some(x) => return Ok(x.pow(secret) % self.p),
-            None => return Err(Box::new(DHError::SecretNotComputed)),
-        }
+}
+
+/// two parameter sets are the same group when p, g and q agree, the private value length and
+/// other generation details do not matter; a known q is part of the group as it decides how
+/// strictly peer values are validated
+impl PartialEq for Parameters {
+    fn eq(&self, other: &Self) -> bool {
+        self.p == other.p && self.g == other.g && self.q == other.q
     }
-}   
+}
+
+impl Eq for Parameters {}
+
 #[derive(Debug)]
-enum DHError {
-    SecretNotComputed,
+pub enum DHError {
     InvalidP,
-    InvalidG
+    InvalidG,
+    CannotFactorOrder,
+    InvalidBitLength,
+    NotSafePrime,
+    InvalidQ,
+    InvalidPrivateKey,
+    ParameterMismatch,
+    Entropy(io::Error),
+    PublicKeyOutOfRange,
+    PublicKeyNotInSubgroup,
+    PublicKeyLowOrder,
+    DerivedKeyTooLong,
+    EncodingTooLong,
+    NotReduced,
+    InvalidDer,
+    InvalidPem,
+    InvalidCofactor,
+    MissingSubgroupOrder,
+    InvalidPrivateValueLength,
 }
 
 impl Display for DHError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::SecretNotComputed => write!(f, "Pub Value not yet computed with P and G values"),
             Self::InvalidP => write!(f, "Invalid value of P, not prime"),
             Self::InvalidG => write!(f, "Invalid value of G, not primitive root"),
+            Self::CannotFactorOrder => write!(f, "Could not factor P - 1 to check G"),
+            Self::InvalidBitLength => write!(f, "Bit length too small for the requested prime"),
+            Self::NotSafePrime => write!(f, "Invalid value of P, (P - 1) / 2 not prime"),
+            Self::InvalidQ => write!(f, "Invalid value of Q, not a prime divisor of P - 1"),
+            Self::InvalidPrivateKey => write!(f, "Private key out of range for the group"),
+            Self::ParameterMismatch => write!(f, "Keys belong to different group parameters"),
+            Self::Entropy(err) => write!(f, "Could not read from the OS entropy source: {}", err),
+            Self::PublicKeyOutOfRange => write!(f, "Invalid public key, not in [2, P - 2]"),
+            Self::PublicKeyNotInSubgroup => write!(f, "Invalid public key, not in the subgroup of order Q"),
+            Self::PublicKeyLowOrder => write!(f, "Invalid public key, order too small"),
+            Self::DerivedKeyTooLong => write!(f, "Requested key length too long for the KDF"),
+            Self::EncodingTooLong => write!(f, "Encoded value longer than P"),
+            Self::NotReduced => write!(f, "Encoded value not reduced modulo P"),
+            Self::InvalidDer => write!(f, "Malformed DER encoding"),
+            Self::InvalidPem => write!(f, "Malformed PEM encoding"),
+            Self::InvalidCofactor => write!(f, "Invalid value of J, not (P - 1) / Q"),
+            Self::MissingSubgroupOrder => write!(f, "Parameters have no subgroup order Q"),
+            Self::InvalidPrivateValueLength => write!(f, "Private value length must be between 1 and the bit length of P"),
         }
     }
 }
 
-impl Error for DHError {}
\ No newline at end of file
+impl Error for DHError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Entropy(err) => Some(err),
+            _ => None,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,65 @@
+use crate::{bigint::BigUint, montgomery::MontgomeryContext};
+
+/// compute base^exp mod modulus by square and multiply, reducing after every step
+/// so intermediates never grow past twice the size of the modulus
+/// moduli that fit in a single word go through 128 bit arithmetic, larger odd moduli through
+/// Montgomery multiplication and larger even moduli through plain BigUint division
+pub fn mod_pow(base: &BigUint, exp: &BigUint, modulus: &BigUint) -> BigUint {
+    assert!(!modulus.is_zero(), "modulus must be non-zero");
+    match modulus.to_u64() {
+        Some(m) => {
+            let base = (base % modulus).to_u64().unwrap();
+            BigUint::from_u64(mod_pow_u64_big_exp(base, exp, m))
+        }
+        None => mod_pow_big(base, exp, modulus),
+    }
+}
+
+/// compute base^exp mod modulus without branches or memory accesses that depend on the
+/// exponent, for use whenever the exponent is a secret
+/// only odd moduli can be handled in constant time, so an even modulus gives None rather
+/// than quietly falling back to the variable time `mod_pow`
+pub fn mod_pow_ct(base: &BigUint, exp: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    assert!(!modulus.is_zero(), "modulus must be non-zero");
+    MontgomeryContext::new(modulus).map(|ctx| ctx.pow_ct(base, exp))
+}
+
+/// word sized modular exponentiation using a 128 bit intermediate for each product
+pub fn mod_pow_u64(base: u64, exp: u64, modulus: u64) -> u64 {
+    mod_pow_u64_big_exp(base, &BigUint::from_u64(exp), modulus)
+}
+
+/// multiply two words modulo a third without overflowing
+pub fn mul_mod_u64(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+
+fn mod_pow_u64_big_exp(base: u64, exp: &BigUint, modulus: u64) -> u64 {
+    if modulus == 1 {
+        return 0;
+    }
+    let base = base % modulus;
+    let mut res = 1;
+    for i in (0..exp.bits()).rev() {
+        res = mul_mod_u64(res, res, modulus);
+        if exp.bit(i) {
+            res = mul_mod_u64(res, base, modulus);
+        }
+    }
+    res
+}
+
+fn mod_pow_big(base: &BigUint, exp: &BigUint, modulus: &BigUint) -> BigUint {
+    if let Some(ctx) = MontgomeryContext::new(modulus) {
+        return ctx.pow(base, exp);
+    }
+    let base = base % modulus;
+    let mut res = BigUint::one();
+    for i in (0..exp.bits()).rev() {
+        res = &(&res * &res) % modulus;
+        if exp.bit(i) {
+            res = &(&res * &base) % modulus;
+        }
+    }
+    res
+}
@@ -0,0 +1,235 @@
+use std::hint::black_box;
+
+use crate::{bigint::BigUint, zeroize::zeroize_limbs};
+
+/// width of the exponent windows used by `MontgomeryContext::pow`
+const WINDOW_BITS: usize = 4;
+
+/// precomputed values for Montgomery multiplication under a fixed odd modulus
+/// with R = 2^(64 * limbs of the modulus), a product a*b*R^-1 mod m is computed
+/// with multiplications and shifts only, so repeated exponentiations under the same
+/// prime never have to divide
+#[derive(Clone, Debug)]
+pub struct MontgomeryContext {
+    modulus: BigUint,
+    /// limbs of the modulus, every value in Montgomery form has exactly this many limbs
+    m: Vec<u64>,
+    /// -m^-1 mod 2^64
+    m_inv: u64,
+    /// R mod m, the Montgomery form of one
+    r: BigUint,
+    /// R^2 mod m, used to convert into Montgomery form
+    r2: BigUint,
+}
+
+impl MontgomeryContext {
+    /// build the context for `modulus`, None if the modulus is even as R would not be invertible
+    pub fn new(modulus: &BigUint) -> Option<Self> {
+        if modulus.is_even() {
+            return None;
+        }
+        let m = modulus.limbs().to_vec();
+        // Newton iteration, each step doubles the number of correct low bits of the inverse
+        // and an odd number is its own inverse mod 8 which gives the first three
+        let mut inv = m[0];
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(m[0].wrapping_mul(inv)));
+        }
+        let r = &(&BigUint::one() << (64 * m.len())) % modulus;
+        let r2 = &(&r * &r) % modulus;
+        Some(Self {
+            modulus: modulus.clone(),
+            m,
+            m_inv: inv.wrapping_neg(),
+            r,
+            r2,
+        })
+    }
+
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// R mod m
+    pub fn r(&self) -> &BigUint {
+        &self.r
+    }
+
+    /// R^2 mod m
+    pub fn r2(&self) -> &BigUint {
+        &self.r2
+    }
+
+    /// -m^-1 mod 2^64
+    pub fn m_inv(&self) -> u64 {
+        self.m_inv
+    }
+
+    /// compute a*b mod m
+    /// a*b*R^-1 followed by a product with R^2 gives a*b without converting either operand,
+    /// and only operands that are not already below m pay for a division
+    pub fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let ab = self.mont_mul(&self.reduced(a), &self.reduced(b));
+        BigUint::from_limbs(self.mont_mul(&ab, &self.to_fixed(&self.r2)))
+    }
+
+    /// compute base^exp mod m with a fixed 4 bit window over the exponent
+    pub fn pow(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        self.out_of_montgomery(&self.pow_fixed(base, exp))
+    }
+
+    /// a*R mod m, for loops that stay in Montgomery form across many products
+    pub(crate) fn enter(&self, a: &BigUint) -> BigUint {
+        BigUint::from_limbs(self.to_montgomery(a))
+    }
+
+    /// a*b*R^-1 mod m for a and b below m, the product of two values in Montgomery form
+    pub(crate) fn mul_montgomery(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        BigUint::from_limbs(self.mont_mul(&self.to_fixed(a), &self.to_fixed(b)))
+    }
+
+    /// the square of a value in Montgomery form
+    pub(crate) fn square_montgomery(&self, a: &BigUint) -> BigUint {
+        let a = self.to_fixed(a);
+        BigUint::from_limbs(self.mont_mul(&a, &a))
+    }
+
+    /// base^exp*R mod m, `pow` left in Montgomery form
+    pub(crate) fn pow_montgomery(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        BigUint::from_limbs(self.pow_fixed(base, exp))
+    }
+
+    fn pow_fixed(&self, base: &BigUint, exp: &BigUint) -> Vec<u64> {
+        let mut table = Vec::with_capacity(1 << WINDOW_BITS);
+        table.push(self.to_fixed(&self.r));
+        table.push(self.to_montgomery(base));
+        for i in 2..1 << WINDOW_BITS {
+            table.push(self.mont_mul(&table[i - 1], &table[1]));
+        }
+
+        let mut res = table[0].clone();
+        let windows = exp.bits().div_ceil(WINDOW_BITS);
+        for w in (0..windows).rev() {
+            for _ in 0..WINDOW_BITS {
+                res = self.mont_mul(&res, &res);
+            }
+            let digit = (0..WINDOW_BITS).fold(0, |acc, i| acc | (exp.bit(w * WINDOW_BITS + i) as usize) << i);
+            if digit != 0 {
+                res = self.mont_mul(&res, &table[digit]);
+            }
+        }
+        res
+    }
+
+    /// compute base^exp mod m in constant time with respect to the exponent
+    /// a Montgomery ladder performs one multiplication and one squaring for every bit
+    /// of a fixed width exponent, and the two accumulators are exchanged with a masked
+    /// swap, so neither the branches taken nor the memory touched depend on secret bits
+    /// the width is the larger of the modulus and exponent widths in limbs, which for
+    /// exponents below the modulus only ever reveals the size of the modulus
+    pub fn pow_ct(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        let width = self.m.len().max(exp.limbs().len());
+        let mut exp_limbs = exp.limbs().to_vec();
+        exp_limbs.resize(width, 0);
+
+        let mut r0 = self.to_fixed(&self.r);
+        let mut r1 = self.to_montgomery(base);
+        for i in (0..width * 64).rev() {
+            let bit = (exp_limbs[i / 64] >> (i % 64)) & 1;
+            cswap(&mut r0, &mut r1, bit);
+            r1 = self.mont_mul(&r0, &r1);
+            r0 = self.mont_mul(&r0, &r0);
+            cswap(&mut r0, &mut r1, bit);
+        }
+        let res = self.out_of_montgomery(&r0);
+        // the accumulators and the exponent copy are derived from the secret
+        zeroize_limbs(&mut exp_limbs);
+        zeroize_limbs(&mut r0);
+        zeroize_limbs(&mut r1);
+        res
+    }
+
+    /// a*R mod m as fixed width limbs
+    fn to_montgomery(&self, a: &BigUint) -> Vec<u64> {
+        self.mont_mul(&self.reduced(a), &self.to_fixed(&self.r2))
+    }
+
+    /// a mod m as fixed width limbs, dividing only when a is not already reduced
+    fn reduced(&self, a: &BigUint) -> Vec<u64> {
+        match *a < self.modulus {
+            true => self.to_fixed(a),
+            false => self.to_fixed(&(a % &self.modulus)),
+        }
+    }
+
+    /// a*R^-1 mod m, leaving Montgomery form
+    fn out_of_montgomery(&self, a: &[u64]) -> BigUint {
+        let mut one = vec![0u64; self.m.len()];
+        one[0] = 1;
+        BigUint::from_limbs(self.mont_mul(a, &one))
+    }
+
+    /// pad a reduced value to the width of the modulus
+    fn to_fixed(&self, a: &BigUint) -> Vec<u64> {
+        let mut limbs = a.limbs().to_vec();
+        limbs.resize(self.m.len(), 0);
+        limbs
+    }
+
+    /// a*b*R^-1 mod m using coarsely integrated operand scanning (CIOS)
+    fn mont_mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let n = self.m.len();
+        let mut t = vec![0u64; n + 2];
+        for limb in b {
+            let mut carry: u128 = 0;
+            for j in 0..n {
+                let s = t[j] as u128 + a[j] as u128 * *limb as u128 + carry;
+                t[j] = s as u64;
+                carry = s >> 64;
+            }
+            let s = t[n] as u128 + carry;
+            t[n] = s as u64;
+            t[n + 1] = (s >> 64) as u64;
+
+            // add mu*m so the lowest limb becomes zero, then shift down one limb
+            let mu = t[0].wrapping_mul(self.m_inv);
+            let s = t[0] as u128 + mu as u128 * self.m[0] as u128;
+            let mut carry = s >> 64;
+            for j in 1..n {
+                let s = t[j] as u128 + mu as u128 * self.m[j] as u128 + carry;
+                t[j - 1] = s as u64;
+                carry = s >> 64;
+            }
+            let s = t[n] as u128 + carry;
+            t[n - 1] = s as u64;
+            t[n] = t[n + 1] + (s >> 64) as u64;
+        }
+
+        // the result is below 2m, so subtract m once and keep the difference unless it
+        // borrowed past the top limb, selecting with a mask rather than a branch
+        let mut diff = vec![0u64; n];
+        let mut borrow = false;
+        for j in 0..n {
+            let (d, b1) = t[j].overflowing_sub(self.m[j]);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+            diff[j] = d;
+            borrow = b1 || b2;
+        }
+        let (_, underflow) = t[n].overflowing_sub(borrow as u64);
+        let keep = black_box((underflow as u64).wrapping_neg());
+        for j in 0..n {
+            diff[j] = (t[j] & keep) | (diff[j] & !keep);
+        }
+        diff
+    }
+}
+
+/// swap `a` and `b` when `choice` is 1 and leave them when it is 0, without branching
+fn cswap(a: &mut [u64], b: &mut [u64], choice: u64) {
+    let mask = black_box(choice.wrapping_neg());
+    for (x, y) in a.iter_mut().zip(b.iter_mut()) {
+        let t = (*x ^ *y) & mask;
+        *x ^= t;
+        *y ^= t;
+    }
+}
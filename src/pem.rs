@@ -0,0 +1,74 @@
+use std::error;
+
+use crate::DHError;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// base64 characters per line of PEM output, as RFC 7468 requires
+const LINE_LEN: usize = 64;
+
+/// the DER contents of the first block labelled `label` in RFC 7468 textual encoding
+/// text around the block is ignored, as is whitespace inside it
+pub(crate) fn decode(text: &str, label: &str) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let start = text.find(&begin).ok_or(DHError::InvalidPem)? + begin.len();
+    let stop = start + text[start..].find(&end).ok_or(DHError::InvalidPem)?;
+    base64_decode(&text[start..stop])
+}
+
+/// wrap DER in a block labelled `label`
+pub(crate) fn encode(der: &[u8], label: &str) -> String {
+    let body = base64_encode(der);
+    let mut out = format!("-----BEGIN {}-----\n", label);
+    for line in body.as_bytes().chunks(LINE_LEN) {
+        // base64 output is ASCII
+        out.push_str(std::str::from_utf8(line).unwrap());
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {}-----\n", label));
+    out
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |acc, (i, b)| acc | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f] as char),
+                false => out.push('='),
+            }
+        }
+    }
+    out
+}
+
+/// strict base64: padding only at the end and unused bits of the last group zero
+fn base64_decode(text: &str) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    let chars: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !chars.len().is_multiple_of(4) {
+        return Err(Box::new(DHError::InvalidPem));
+    }
+    let mut out = Vec::with_capacity(chars.len() / 4 * 3);
+    let groups = chars.len() / 4;
+    for (index, group) in chars.chunks(4).enumerate() {
+        let padding = group.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && index + 1 != groups) {
+            return Err(Box::new(DHError::InvalidPem));
+        }
+        let mut n = 0u32;
+        for c in &group[..4 - padding] {
+            let value = ALPHABET.iter().position(|a| a == c).ok_or(DHError::InvalidPem)?;
+            n = (n << 6) | value as u32;
+        }
+        n <<= 6 * padding;
+        let bytes = n.to_be_bytes();
+        let len = 3 - padding;
+        if bytes[1 + len..].iter().any(|b| *b != 0) {
+            return Err(Box::new(DHError::InvalidPem));
+        }
+        out.extend_from_slice(&bytes[1..1 + len]);
+    }
+    Ok(out)
+}
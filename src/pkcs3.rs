@@ -0,0 +1,77 @@
+use std::error;
+
+use crate::{
+    der::{self, Reader},
+    pem, BigUint, DHError, Parameters,
+};
+
+/// the label OpenSSL writes around PKCS#3 parameters
+const PEM_LABEL: &str = "DH PARAMETERS";
+
+/// PKCS#3 encoding, as used by `openssl dhparam`
+///
+/// DHParameter ::= SEQUENCE {
+///     prime INTEGER, -- p
+///     base INTEGER, -- g
+///     privateValueLength INTEGER OPTIONAL }
+///
+/// PKCS#3 has no field for the subgroup order, so q is never written, and on import it is
+/// only restored for the built-in groups, recognised by their p and g
+impl Parameters {
+    /// parse a DER encoded DHParameter
+    /// p must be odd and g in [2, p - 2]
+    /// imported built-in groups such as ffdhe2048 get their q back, so they compare equal to
+    /// the built-in parameters and pass `is_valid`; other parameters come without q, and as
+    /// `openssl dhparam` picks a g of order (p - 1) / 2 they should be checked with
+    /// `is_valid_safe_prime`, whose q can then be attached with `with_subgroup_order`
+    pub fn from_pkcs3_der(der: &[u8]) -> Result<Self, Box<dyn error::Error>> {
+        let mut outer = Reader::new(der);
+        let mut seq = outer.read_sequence()?;
+        outer.finish()?;
+        let p = seq.read_integer()?;
+        let g = seq.read_integer()?;
+        let length = match seq.is_empty() {
+            true => None,
+            false => Some(seq.read_integer()?),
+        };
+        seq.finish()?;
+        Parameters::check_imported(&p, &g)?;
+
+        let mut params = match Parameters::builtin_group(&p, &g) {
+            Some(group) => group,
+            None => Parameters::new(p, g),
+        };
+        if let Some(length) = length {
+            // a private value needs at least one bit and must be shorter than p
+            let bits = length
+                .to_u64()
+                .filter(|bits| *bits > 0 && *bits < params.p().bits() as u64)
+                .ok_or(DHError::InvalidPrivateValueLength)?;
+            params = params.with_private_value_length(bits as usize);
+        }
+        Ok(params)
+    }
+
+    /// encode as a DER DHParameter, including privateValueLength when it is set
+    pub fn to_pkcs3_der(&self) -> Vec<u8> {
+        let mut contents = Vec::new();
+        der::write_integer(&mut contents, self.p());
+        der::write_integer(&mut contents, self.g());
+        if let Some(bits) = self.private_value_length() {
+            der::write_integer(&mut contents, &BigUint::from_u64(bits as u64));
+        }
+        let mut out = Vec::new();
+        der::write(&mut out, der::TAG_SEQUENCE, &contents);
+        out
+    }
+
+    /// parse the first "DH PARAMETERS" PEM block in `pem`, such as a dhparam.pem file
+    pub fn from_pkcs3_pem(pem: &str) -> Result<Self, Box<dyn error::Error>> {
+        Parameters::from_pkcs3_der(&pem::decode(pem, PEM_LABEL)?)
+    }
+
+    /// encode as a "DH PARAMETERS" PEM block
+    pub fn to_pkcs3_pem(&self) -> String {
+        pem::encode(&self.to_pkcs3_der(), PEM_LABEL)
+    }
+}
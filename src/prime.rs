@@ -0,0 +1,519 @@
+use std::error;
+
+use crate::{
+    bigint::BigUint,
+    modular::{mod_pow_u64, mul_mod_u64},
+    montgomery::MontgomeryContext,
+    rng::{random_bits, Rng},
+    DHError,
+};
+
+/// suggested number of Miller-Rabin rounds for `PrimalityPolicy::MillerRabin` past 64 bits
+pub const DEFAULT_MILLER_RABIN_ROUNDS: usize = 64;
+
+/// the first 512 primes, used for trial division and as Miller-Rabin bases
+pub const SMALL_PRIMES: [u64; 512] = small_primes();
+
+/// iterations of Pollard's rho spent on a single composite before `prime_factors` gives up
+const RHO_ITERATIONS: usize = 1 << 16;
+
+/// below this size prime generation draws candidates directly instead of sieving,
+/// as the sieve would reject the small primes themselves
+const SIEVE_MIN_BITS: usize = 16;
+
+/// how far generation steps up from a random start before drawing a new one
+const SIEVE_WINDOW: u64 = 1 << 16;
+
+/// bases that make Miller-Rabin deterministic for every n below 2^32
+const WITNESSES_U32: [u64; 3] = [2, 7, 61];
+
+/// bases that make Miller-Rabin deterministic for every n below 2^64
+const WITNESSES_U64: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+const fn small_primes<const N: usize>() -> [u64; N] {
+    let mut primes = [0u64; N];
+    let mut count = 0;
+    let mut candidate = 2;
+    while count < N {
+        let mut i = 0;
+        let mut is_prime = true;
+        while i < count && primes[i] * primes[i] <= candidate {
+            if candidate % primes[i] == 0 {
+                is_prime = false;
+                break;
+            }
+            i += 1;
+        }
+        if is_prime {
+            primes[count] = candidate;
+            count += 1;
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+/// how the validity checks decide whether a modulus is prime
+/// defaults to Baillie-PSW, as Miller-Rabin with fixed bases can be fooled by crafted composites
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrimalityPolicy {
+    /// Miller-Rabin with the given number of rounds, see `miller_rabin`
+    MillerRabin(usize),
+    /// Baillie-PSW, which has no known pseudoprimes, see `baillie_psw`
+    #[default]
+    BailliePsw,
+}
+
+impl PrimalityPolicy {
+    pub fn is_probable_prime(&self, n: &BigUint) -> bool {
+        match self {
+            Self::MillerRabin(rounds) => miller_rabin(n, *rounds),
+            Self::BailliePsw => baillie_psw(n),
+        }
+    }
+}
+
+/// Miller-Rabin probable prime test
+/// numbers that fit in 64 bits are tested against fixed witness sets, which makes the
+/// answer exact; larger numbers are tested against the first `rounds` primes as bases
+/// the bases are fixed rather than random, so the 4^-rounds error bound only holds for
+/// randomly chosen n: composites built to pass every small prime base are known, and
+/// untrusted input should go through `baillie_psw` instead
+/// at least one round is always run, so 0 rounds cannot accept a composite unchecked
+pub fn miller_rabin(n: &BigUint, rounds: usize) -> bool {
+    if let Some(n) = n.to_u64() {
+        return miller_rabin_u64(n);
+    }
+    if n.is_even() {
+        return false;
+    }
+    if SMALL_PRIMES.iter().any(|p| n.div_rem_u64(*p).1 == 0) {
+        return false;
+    }
+
+    let ctx = MontgomeryContext::new(n).unwrap();
+    let n_minus_one = n - &BigUint::one();
+    let s = n_minus_one.trailing_zeros();
+    let d = &n_minus_one >> s;
+
+    SMALL_PRIMES
+        .iter()
+        .take(rounds.clamp(1, SMALL_PRIMES.len()))
+        .all(|a| strong_probable_prime(&ctx, &BigUint::from_u64(*a), &d, s))
+}
+
+/// a single strong probable prime test of n to `base`, with n - 1 = d * 2^s
+/// the squarings stay in Montgomery form and are compared against the Montgomery forms
+/// of 1 and n - 1, which are R mod n and n - (R mod n)
+pub(crate) fn strong_probable_prime(ctx: &MontgomeryContext, base: &BigUint, d: &BigUint, s: usize) -> bool {
+    let one = ctx.r();
+    let minus_one = ctx.modulus() - one;
+    let mut x = ctx.pow_montgomery(base, d);
+    if x == *one || x == minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = ctx.square_montgomery(&x);
+        if x == minus_one {
+            return true;
+        }
+        if x == *one {
+            return false;
+        }
+    }
+    false
+}
+
+/// deterministic Miller-Rabin for word sized numbers
+pub fn miller_rabin_u64(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in WITNESSES_U64 {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let witnesses: &[u64] = match n <= u32::MAX as u64 {
+        true => &WITNESSES_U32,
+        false => &WITNESSES_U64,
+    };
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    'witness: for a in witnesses {
+        if a.is_multiple_of(n) {
+            continue;
+        }
+        let mut x = mod_pow_u64(*a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod_u64(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Baillie-PSW probable prime test: a strong probable prime test to base 2 followed by a
+/// strong Lucas probable prime test with Selfridge's parameters
+/// numbers that fit in 64 bits get the exact deterministic Miller-Rabin answer instead
+pub fn baillie_psw(n: &BigUint) -> bool {
+    if let Some(n) = n.to_u64() {
+        return miller_rabin_u64(n);
+    }
+    if n.is_even() {
+        return false;
+    }
+    if SMALL_PRIMES.iter().any(|p| n.div_rem_u64(*p).1 == 0) {
+        return false;
+    }
+
+    let ctx = MontgomeryContext::new(n).unwrap();
+    let n_minus_one = n - &BigUint::one();
+    let s = n_minus_one.trailing_zeros();
+    let d = &n_minus_one >> s;
+    if !strong_probable_prime(&ctx, &BigUint::from_u64(2), &d, s) {
+        return false;
+    }
+    strong_lucas_probable_prime(n)
+}
+
+/// Jacobi symbol (a/n) for odd n, returns -1, 0 or 1
+pub fn jacobi(a: &BigUint, n: &BigUint) -> i32 {
+    assert!(n.is_odd(), "the Jacobi symbol is only defined for odd n");
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut res = 1;
+    while !a.is_zero() {
+        let twos = a.trailing_zeros();
+        a = &a >> twos;
+        // (2/n) is -1 exactly when n is 3 or 5 mod 8
+        let n_mod_8 = n.limbs()[0] & 7;
+        if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            res = -res;
+        }
+        // quadratic reciprocity flips the sign when both are 3 mod 4
+        std::mem::swap(&mut a, &mut n);
+        if a.limbs()[0] & 3 == 3 && n.limbs()[0] & 3 == 3 {
+            res = -res;
+        }
+        a = &a % &n;
+    }
+    match n.is_one() {
+        true => res,
+        false => 0,
+    }
+}
+
+/// Jacobi symbol of a small signed numerator, (-a/n) = (-1/n)(a/n)
+fn jacobi_signed(a: i64, n: &BigUint) -> i32 {
+    let res = jacobi(&BigUint::from_u64(a.unsigned_abs()), n);
+    match a < 0 && n.limbs()[0] & 3 == 3 {
+        true => -res,
+        false => res,
+    }
+}
+
+/// a small signed value reduced into [0, n)
+fn signed_mod(a: i64, n: &BigUint) -> BigUint {
+    let abs = &BigUint::from_u64(a.unsigned_abs()) % n;
+    match a < 0 && !abs.is_zero() {
+        true => n - &abs,
+        false => abs,
+    }
+}
+
+fn sub_mod(a: &BigUint, b: &BigUint, n: &BigUint) -> BigUint {
+    match a >= b {
+        true => a - b,
+        false => &(a + n) - b,
+    }
+}
+
+/// x / 2 mod n for odd n
+fn half_mod(x: &BigUint, n: &BigUint) -> BigUint {
+    match x.is_even() {
+        true => x >> 1,
+        false => &(x + n) >> 1,
+    }
+}
+
+/// strong Lucas probable prime test for odd n that is not divisible by small primes,
+/// with P = 1 and Q = (1 - D) / 4 where D is the first of 5, -7, 9, -11, ...
+/// whose Jacobi symbol (D/n) is -1 (Selfridge's method A)
+fn strong_lucas_probable_prime(n: &BigUint) -> bool {
+    // for a perfect square no D ever has (D/n) = -1
+    let root = n.sqrt();
+    if &root * &root == *n {
+        return false;
+    }
+
+    let mut d: i64 = 5;
+    loop {
+        match jacobi_signed(d, n) {
+            -1 => break,
+            // a shared factor, n is composite unless it is |D| itself, which the
+            // trial division by small primes has already ruled out
+            0 => return false,
+            _ => {
+                d = match d > 0 {
+                    true => -(d + 2),
+                    false => -d + 2,
+                }
+            }
+        }
+    }
+    let q = (1 - d) / 4;
+    let d_mod = signed_mod(d, n);
+    let q_mod = signed_mod(q, n);
+
+    // n + 1 = k * 2^s with k odd
+    let n_plus_one = n + &BigUint::one();
+    let s = n_plus_one.trailing_zeros();
+    let k = &n_plus_one >> s;
+
+    // walk the bits of k computing U_k, V_k and Q^k by doubling and incrementing
+    let mut u = BigUint::zero();
+    let mut v = BigUint::from_u64(2);
+    let mut q_k = BigUint::one();
+    for i in (0..k.bits()).rev() {
+        // U_2m = U_m V_m, V_2m = V_m^2 - 2 Q^m
+        u = &(&u * &v) % n;
+        v = sub_mod(&(&(&v * &v) % n), &(&(&q_k << 1) % n), n);
+        q_k = &(&q_k * &q_k) % n;
+        if k.bit(i) {
+            // U_m+1 = (P U_m + V_m) / 2, V_m+1 = (D U_m + P V_m) / 2 with P = 1
+            let next_u = half_mod(&(&(&u + &v) % n), n);
+            let next_v = half_mod(&(&(&(&d_mod * &u) + &v) % n), n);
+            u = next_u;
+            v = next_v;
+            q_k = &(&q_k * &q_mod) % n;
+        }
+    }
+
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = sub_mod(&(&(&v * &v) % n), &(&(&q_k << 1) % n), n);
+        if v.is_zero() {
+            return true;
+        }
+        q_k = &(&q_k * &q_k) % n;
+    }
+    false
+}
+
+/// the distinct prime factors of n in ascending order
+/// small factors are found by trial division and the rest with Pollard's rho, which only
+/// succeeds when every composite part has a factor within reach of `RHO_ITERATIONS`
+/// steps, so None means n could not be factored rather than that it has no factors
+pub fn prime_factors(n: &BigUint) -> Option<Vec<BigUint>> {
+    let mut factors = Vec::new();
+    let mut rest = n.clone();
+    if rest.is_zero() {
+        return None;
+    }
+    for p in SMALL_PRIMES {
+        let (q, r) = rest.div_rem_u64(p);
+        if r != 0 {
+            continue;
+        }
+        factors.push(BigUint::from_u64(p));
+        rest = q;
+        loop {
+            let (q, r) = rest.div_rem_u64(p);
+            if r != 0 {
+                break;
+            }
+            rest = q;
+        }
+    }
+
+    let mut pending = vec![rest];
+    while let Some(m) = pending.pop() {
+        if m.is_one() {
+            continue;
+        }
+        if baillie_psw(&m) {
+            factors.push(m);
+            continue;
+        }
+        let d = pollard_rho(&m)?;
+        let mut cofactor = &m / &d;
+        // strip every copy of the new factor's parts so they are not reported twice
+        while (&cofactor % &d).is_zero() {
+            cofactor = &cofactor / &d;
+        }
+        pending.push(d);
+        pending.push(cofactor);
+    }
+
+    factors.sort();
+    factors.dedup();
+    Some(factors)
+}
+
+/// find a non-trivial factor of an odd composite with Brent's variant of Pollard's rho
+fn pollard_rho(n: &BigUint) -> Option<BigUint> {
+    let root = n.sqrt();
+    if &root * &root == *n {
+        return Some(root);
+    }
+    // the walk runs entirely in Montgomery form: x -> x^2 + c maps x*R to x^2*R + c*R, and
+    // as R is coprime to n the gcds come out the same as for the plain values
+    let ctx = MontgomeryContext::new(n)?;
+    for c in 1..=16u64 {
+        let c = ctx.enter(&BigUint::from_u64(c));
+        let step = |x: &BigUint| {
+            let next = &ctx.square_montgomery(x) + &c;
+            match next >= *n {
+                true => &next - n,
+                false => next,
+            }
+        };
+        let mut y = ctx.enter(&BigUint::from_u64(2));
+        let mut product = ctx.r().clone();
+        let mut d = BigUint::one();
+        let mut power = 1;
+        let mut iterations = 0;
+        while d.is_one() && iterations < RHO_ITERATIONS {
+            let x = y.clone();
+            for _ in 0..power {
+                y = step(&y);
+                let diff = match x >= y {
+                    true => &x - &y,
+                    false => &y - &x,
+                };
+                product = ctx.mul_montgomery(&product, &diff);
+                iterations += 1;
+                // take the gcd in batches of 128 steps rather than after every step
+                if iterations % 128 == 0 {
+                    d = product.gcd(n);
+                    if !d.is_one() {
+                        break;
+                    }
+                }
+            }
+            power *= 2;
+        }
+        if d.is_one() {
+            d = product.gcd(n);
+        }
+        // a gcd of n means the batch overshot the collision, retry with another constant,
+        // while a gcd of one means the iteration budget ran out
+        if d.is_one() {
+            return None;
+        }
+        if d != *n {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// random prime of exactly `bits` bits
+/// odd candidates are sieved against `SMALL_PRIMES` while stepping upward from a random
+/// start, and only candidates that survive the sieve go through the default primality test,
+/// Baillie-PSW, while primes below `SIEVE_MIN_BITS` are checked exactly with `miller_rabin_u64`
+pub fn generate_prime<R: Rng + ?Sized>(bits: usize, rng: &mut R) -> Result<BigUint, Box<dyn error::Error>> {
+    if bits < 2 {
+        return Err(Box::new(DHError::InvalidBitLength));
+    }
+    if bits < SIEVE_MIN_BITS {
+        return generate_small(bits, rng, miller_rabin_u64);
+    }
+    loop {
+        let start = random_odd_with_top_bit(bits, rng)?;
+        let residues: Vec<u64> = SMALL_PRIMES.iter().map(|p| start.div_rem_u64(*p).1).collect();
+        for delta in (0..SIEVE_WINDOW).step_by(2) {
+            let sieved = SMALL_PRIMES
+                .iter()
+                .zip(&residues)
+                .any(|(p, r)| (r + delta) % p == 0);
+            if sieved {
+                continue;
+            }
+            let candidate = &start + &BigUint::from_u64(delta);
+            if candidate.bits() != bits {
+                break;
+            }
+            if PrimalityPolicy::default().is_probable_prime(&candidate) {
+                return Ok(candidate);
+            }
+        }
+    }
+}
+
+/// random safe prime p = 2q + 1 of exactly `bits` bits, where q is also prime
+/// the sieve rejects q whenever q or 2q + 1 has a small factor, and the cheap base 2
+/// test on p runs before the full tests so most surviving candidates are dropped early
+pub fn generate_safe_prime<R: Rng + ?Sized>(bits: usize, rng: &mut R) -> Result<BigUint, Box<dyn error::Error>> {
+    if bits < 3 {
+        return Err(Box::new(DHError::InvalidBitLength));
+    }
+    if bits < SIEVE_MIN_BITS {
+        return generate_small(bits, rng, |p| miller_rabin_u64(p) && miller_rabin_u64(p / 2));
+    }
+    loop {
+        let start = random_odd_with_top_bit(bits - 1, rng)?;
+        let residues: Vec<u64> = SMALL_PRIMES.iter().map(|p| start.div_rem_u64(*p).1).collect();
+        for delta in (0..SIEVE_WINDOW).step_by(2) {
+            // q = start + delta must avoid 0 mod s and p = 2q + 1 must avoid 0 mod s,
+            // which means q must also avoid (s - 1) / 2 mod s
+            let sieved = SMALL_PRIMES.iter().zip(&residues).any(|(s, r)| {
+                let q_mod = (r + delta) % s;
+                q_mod == 0 || q_mod == (s - 1) / 2
+            });
+            if sieved {
+                continue;
+            }
+            let q = &start + &BigUint::from_u64(delta);
+            if q.bits() != bits - 1 {
+                break;
+            }
+            let p = &(&q << 1) + &BigUint::one();
+            if !miller_rabin(&p, 1) {
+                continue;
+            }
+            let policy = PrimalityPolicy::default();
+            if policy.is_probable_prime(&q) && policy.is_probable_prime(&p) {
+                return Ok(p);
+            }
+        }
+    }
+}
+
+/// candidates below `SIEVE_MIN_BITS` bits are few enough to draw until one fits
+fn generate_small<R, F>(bits: usize, rng: &mut R, accept: F) -> Result<BigUint, Box<dyn error::Error>>
+where
+    R: Rng + ?Sized,
+    F: Fn(u64) -> bool,
+{
+    loop {
+        let candidate = (rng.next_u64()? & ((1 << bits) - 1)) | (1 << (bits - 1));
+        if accept(candidate) {
+            return Ok(BigUint::from_u64(candidate));
+        }
+    }
+}
+
+/// random odd number of exactly `bits` bits
+fn random_odd_with_top_bit<R: Rng + ?Sized>(bits: usize, rng: &mut R) -> Result<BigUint, Box<dyn error::Error>> {
+    let n = random_bits(rng, bits)?;
+    let top = &BigUint::one() << (bits - 1);
+    let n = match n.bit(bits - 1) {
+        true => n,
+        false => &n + &top,
+    };
+    Ok(match n.is_odd() {
+        true => n,
+        false => &n + &BigUint::one(),
+    })
+}
@@ -0,0 +1,120 @@
+use std::{error, fs::File, io::{self, Read}, path::Path};
+
+use crate::{bigint::BigUint, zeroize::zeroize_bytes, DHError};
+
+/// source of random bytes for parameter and key generation
+/// implementations must be cryptographically secure when the output protects secrets
+pub trait Rng {
+    /// fill `dest` entirely with random bytes
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Box<dyn error::Error>>;
+
+    fn next_u64(&mut self) -> Result<u64, Box<dyn error::Error>> {
+        let mut buf = [0u8; 8];
+        self.fill_bytes(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+}
+
+impl<R: Rng + ?Sized> Rng for &mut R {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Box<dyn error::Error>> {
+        (**self).fill_bytes(dest)
+    }
+}
+
+/// the operating system's cryptographically secure random number generator
+/// on Linux this is the getrandom syscall, with /dev/urandom as the fallback for kernels
+/// that predate it and for every other unix
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OsRng;
+
+impl Rng for OsRng {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Box<dyn error::Error>> {
+        fill_from_os(dest).map_err(entropy_error)
+    }
+}
+
+/// report a failed read from the entropy source as `DHError::Entropy`
+fn entropy_error(err: io::Error) -> Box<dyn error::Error> {
+    Box::new(DHError::Entropy(err))
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "x86", target_arch = "aarch64", target_arch = "arm", target_arch = "riscv64")
+))]
+fn fill_from_os(dest: &mut [u8]) -> io::Result<()> {
+    use std::ffi::c_long;
+
+    #[cfg(target_arch = "x86_64")]
+    const SYS_GETRANDOM: c_long = 318;
+    #[cfg(target_arch = "x86")]
+    const SYS_GETRANDOM: c_long = 355;
+    #[cfg(any(target_arch = "aarch64", target_arch = "riscv64"))]
+    const SYS_GETRANDOM: c_long = 278;
+    #[cfg(target_arch = "arm")]
+    const SYS_GETRANDOM: c_long = 384;
+    const EINTR: i32 = 4;
+    const ENOSYS: i32 = 38;
+
+    extern "C" {
+        fn syscall(number: c_long, ...) -> c_long;
+    }
+
+    let mut filled = 0;
+    while filled < dest.len() {
+        let rest = &mut dest[filled..];
+        // SAFETY: the buffer pointer and length describe `rest`, which stays borrowed for the
+        // whole call, and flags 0 asks the kernel for nothing beyond filling that buffer
+        let res = unsafe { syscall(SYS_GETRANDOM, rest.as_mut_ptr(), rest.len(), 0u32) };
+        if res < 0 {
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(EINTR) => continue,
+                Some(ENOSYS) => return fill_from_urandom(rest),
+                _ => return Err(err),
+            }
+        }
+        filled += res as usize;
+    }
+    Ok(())
+}
+
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "x86", target_arch = "aarch64", target_arch = "arm", target_arch = "riscv64")
+)))]
+fn fill_from_os(dest: &mut [u8]) -> io::Result<()> {
+    fill_from_urandom(dest)
+}
+
+fn fill_from_urandom(dest: &mut [u8]) -> io::Result<()> {
+    fill_from_file(Path::new("/dev/urandom"), dest)
+}
+
+fn fill_from_file(path: &Path, dest: &mut [u8]) -> io::Result<()> {
+    File::open(path)?.read_exact(dest)
+}
+
+/// the fallback `OsRng` takes when getrandom is missing, reading from `path` in place of
+/// /dev/urandom and reporting errors as `OsRng` does, so tests can reach both without an
+/// old kernel
+#[doc(hidden)]
+pub fn fill_from_device(path: &Path, dest: &mut [u8]) -> Result<(), Box<dyn error::Error>> {
+    fill_from_file(path, dest).map_err(entropy_error)
+}
+
+/// a uniformly random number below 2^bits
+/// the byte buffer is wiped before it is freed, as the number may become a private key
+pub(crate) fn random_bits<R: Rng + ?Sized>(rng: &mut R, bits: usize) -> Result<BigUint, Box<dyn error::Error>> {
+    let mut bytes = vec![0u8; bits.div_ceil(8)];
+    if let Err(err) = rng.fill_bytes(&mut bytes) {
+        zeroize_bytes(&mut bytes);
+        return Err(err);
+    }
+    if !bits.is_multiple_of(8) {
+        bytes[0] &= (1u8 << (bits % 8)) - 1;
+    }
+    let n = BigUint::from_bytes_be(&bytes);
+    zeroize_bytes(&mut bytes);
+    Ok(n)
+}
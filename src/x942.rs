@@ -0,0 +1,117 @@
+use std::error;
+
+use crate::{
+    der::{self, Reader},
+    pem, BigUint, DHError, Parameters,
+};
+
+/// the label OpenSSL writes around X9.42 parameters
+const PEM_LABEL: &str = "X9.42 DH PARAMETERS";
+
+/// the seed and counter from the FIPS 186 style generation of p and q, which let a third
+/// party repeat the generation and confirm the primes were not chosen to hide a trapdoor
+/// only seeds of whole bytes are supported, as every generator in practice writes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationParams {
+    seed: Vec<u8>,
+    pgen_counter: u64,
+}
+
+impl ValidationParams {
+    pub fn new(seed: Vec<u8>, pgen_counter: u64) -> Self {
+        Self { seed, pgen_counter }
+    }
+
+    pub fn seed(&self) -> &[u8] {
+        &self.seed
+    }
+
+    /// the number of candidates tried before p was found
+    pub fn pgen_counter(&self) -> u64 {
+        self.pgen_counter
+    }
+}
+
+/// ANSI X9.42 encoding from RFC 3279 section 2.3.3, as written by OpenSSL for DHX keys
+///
+/// DomainParameters ::= SEQUENCE {
+///     p INTEGER, -- odd prime, p = jq + 1
+///     g INTEGER, -- generator, g
+///     q INTEGER, -- factor of p - 1
+///     j INTEGER OPTIONAL, -- subgroup factor
+///     validationParms ValidationParms OPTIONAL }
+///
+/// ValidationParms ::= SEQUENCE {
+///     seed BIT STRING,
+///     pgenCounter INTEGER }
+///
+/// unlike PKCS#3 this carries q, so imported parameters get the subgroup checks
+/// the seed is carried through but the generation is not repeated to check it
+impl Parameters {
+    /// parse a DER encoded DomainParameters
+    /// p must be odd, g in [2, p - 2] and q a divisor of p - 1 of at least 2, call `is_valid`
+    /// to also check primality and that g generates the subgroup of order q
+    pub fn from_x942_der(der: &[u8]) -> Result<Self, Box<dyn error::Error>> {
+        let mut outer = Reader::new(der);
+        let mut seq = outer.read_sequence()?;
+        outer.finish()?;
+        let p = seq.read_integer()?;
+        let g = seq.read_integer()?;
+        let q = seq.read_integer()?;
+        Parameters::check_imported(&p, &g)?;
+        // q = 0 or 1 would make every y pass the y^q = 1 subgroup check
+        if q < BigUint::from_u64(2) || !(&(&p - &BigUint::one()) % &q).is_zero() {
+            return Err(Box::new(DHError::InvalidQ));
+        }
+        let mut params = Parameters::new(p, g).with_subgroup_order(q);
+
+        if seq.peek_tag() == Some(der::TAG_INTEGER) {
+            params = params.with_cofactor(seq.read_integer()?);
+        }
+        if !seq.is_empty() {
+            let mut validation = seq.read_sequence()?;
+            let seed = match validation.read(der::TAG_BIT_STRING)? {
+                // the first byte counts the unused bits at the end
+                [0, seed @ ..] => seed.to_vec(),
+                _ => return Err(Box::new(DHError::InvalidDer)),
+            };
+            let pgen_counter = validation.read_integer()?.to_u64().ok_or(DHError::InvalidDer)?;
+            validation.finish()?;
+            params = params.with_validation_params(ValidationParams::new(seed, pgen_counter));
+        }
+        seq.finish()?;
+        Ok(params)
+    }
+
+    /// encode as a DER DomainParameters, including j and the validation parameters when set
+    /// fails when q is not known, as the structure has no way to leave it out
+    pub fn to_x942_der(&self) -> Result<Vec<u8>, Box<dyn error::Error>> {
+        let q = self.q().ok_or(DHError::MissingSubgroupOrder)?;
+        let mut contents = Vec::new();
+        der::write_integer(&mut contents, self.p());
+        der::write_integer(&mut contents, self.g());
+        der::write_integer(&mut contents, q);
+        if let Some(j) = self.j() {
+            der::write_integer(&mut contents, j);
+        }
+        if let Some(validation) = self.validation_params() {
+            let mut fields = Vec::new();
+            der::write(&mut fields, der::TAG_BIT_STRING, &[&[0], validation.seed()].concat());
+            der::write_integer(&mut fields, &BigUint::from_u64(validation.pgen_counter()));
+            der::write(&mut contents, der::TAG_SEQUENCE, &fields);
+        }
+        let mut out = Vec::new();
+        der::write(&mut out, der::TAG_SEQUENCE, &contents);
+        Ok(out)
+    }
+
+    /// parse the first "X9.42 DH PARAMETERS" PEM block in `pem`
+    pub fn from_x942_pem(pem: &str) -> Result<Self, Box<dyn error::Error>> {
+        Parameters::from_x942_der(&pem::decode(pem, PEM_LABEL)?)
+    }
+
+    /// encode as an "X9.42 DH PARAMETERS" PEM block
+    pub fn to_x942_pem(&self) -> Result<String, Box<dyn error::Error>> {
+        Ok(pem::encode(&self.to_x942_der()?, PEM_LABEL))
+    }
+}
@@ -0,0 +1,32 @@
+use std::{
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+/// overwrite every limb with zero, including any spare capacity a shrinking number may
+/// have left its old limbs in, and leave the vector empty
+/// the writes are volatile and followed by a compiler fence so the optimizer can neither
+/// drop them as dead stores nor move them past the free that follows
+pub(crate) fn zeroize_limbs(limbs: &mut Vec<u64>) {
+    for limb in limbs.iter_mut() {
+        // SAFETY: `limb` is a valid, aligned and exclusively borrowed u64
+        unsafe { ptr::write_volatile(limb, 0) };
+    }
+    for spare in limbs.spare_capacity_mut() {
+        // SAFETY: spare capacity is allocated and exclusively borrowed, and writing a
+        // MaybeUninit never reads the old contents
+        unsafe { ptr::write_volatile(spare, MaybeUninit::new(0)) };
+    }
+    limbs.clear();
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// overwrite every byte with zero, for stack buffers and byte strings holding key material
+pub(crate) fn zeroize_bytes(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        // SAFETY: `byte` is a valid and exclusively borrowed u8
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
@@ -0,0 +1,189 @@
+use diffie_hellman::{BigUint, ChaCha20Rng, Rng};
+
+const MAX: u64 = u64::MAX;
+const HIGH: u64 = 1 << 63;
+
+fn hex(s: &str) -> BigUint {
+    BigUint::from_hex(s).unwrap()
+}
+
+fn limbs(limbs: &[u64]) -> BigUint {
+    BigUint::from_limbs(limbs.to_vec())
+}
+
+fn random(rng: &mut ChaCha20Rng, bits: usize) -> BigUint {
+    let limbs = (0..bits.div_ceil(64)).map(|_| rng.next_u64().unwrap()).collect();
+    &BigUint::from_limbs(limbs) >> (bits.div_ceil(64) * 64 - bits)
+}
+
+fn check_div_rem(a: &BigUint, b: &BigUint) {
+    let (q, r) = a.div_rem(b);
+    assert!(r < *b, "{a:?} / {b:?}");
+    assert_eq!(&(&q * b) + &r, *a, "{a:?} / {b:?}");
+    assert_eq!(&(a - &r) / b, q);
+}
+
+#[test]
+fn div_rem_matches_known_values() {
+    // computed with Python's arbitrary precision integers
+    let vectors = [
+        (
+            "35bf992dc9e9c616612e7696a6cecc1b78e510617311d8a3c2ce6f447ed4d57b1e2feb89414c343c1027c4d1c386bbc4cd613e30d8f16adf91b7584a2265b1f5",
+            "6e63ca828dd5f4b3b2e4b06ce60741c7a87ce42c8218072e8c",
+            "7ca542b7e6cb40383844d04ea7e042a152c75be37de89caf8e41ccd4e33b5ed5d713179739c3b2",
+            "4363b0298e17fe0960ef9f7e746461b0e5035824e603c6b09d",
+        ),
+        (
+            "d4781f9c1f66c0f3459f79b17aeefba91fc803468b6b610a9f7f9270f4eb8b333a8e5446dd4",
+            "14da98f1d3099fdf5ab99254ae901e35c",
+            "a303f5e69f710559ef9657ce9cc7ad2c66e7fec96ff",
+            "687f7eeb0b3610c096859273745b0d30",
+        ),
+    ];
+    for (a, b, q, r) in vectors {
+        assert_eq!(hex(a).div_rem(&hex(b)), (hex(q), hex(r)));
+        assert_eq!(&hex(a) / &hex(b), hex(q));
+        assert_eq!(&hex(a) % &hex(b), hex(r));
+    }
+}
+
+#[test]
+fn div_rem_adds_the_divisor_back_when_the_estimate_is_one_too_large() {
+    // operands whose quotient digit estimate survives the two limb correction and still
+    // overshoots, so the multiply and subtract step borrows out of the window, checked
+    // against a Python model of the algorithm with 64 bit limbs
+    let vectors = [
+        // normalized divisor, no shift
+        (limbs(&[0, 0, 0, 1]), limbs(&[1, 0, HIGH]), limbs(&[1]), limbs(&[MAX, MAX, HIGH - 1])),
+        (limbs(&[0, 0, 0, HIGH - 1]), limbs(&[1, 0, HIGH]), limbs(&[MAX - 2]), limbs(&[3, MAX, HIGH - 1])),
+        // divisors that are shifted into place first
+        (limbs(&[0, 0, 0, 1]), limbs(&[1, 0, 1]), limbs(&[MAX]), limbs(&[1, MAX])),
+        (limbs(&[0, 0, 0, 1]), limbs(&[1, 0, 2]), limbs(&[HIGH - 1]), limbs(&[HIGH + 1, MAX, 1])),
+        (limbs(&[0, 0, 1, 1]), limbs(&[1, 1, 1]), limbs(&[MAX]), limbs(&[1, 0, 1])),
+    ];
+    for (a, b, q, r) in vectors {
+        assert_eq!(a.div_rem(&b), (q, r), "{a:?} / {b:?}");
+    }
+}
+
+#[test]
+fn div_rem_identity_holds_for_random_and_edge_operands() {
+    let mut rng = ChaCha20Rng::from_seed([1; 32]);
+    for (a_bits, b_bits) in [(64, 64), (128, 65), (300, 130), (1000, 999), (4096, 2048), (2048, 64), (130, 127)] {
+        for _ in 0..20 {
+            let a = random(&mut rng, a_bits);
+            let b = random(&mut rng, b_bits);
+            if !b.is_zero() {
+                check_div_rem(&a, &b);
+            }
+        }
+    }
+
+    let edges = [0, 1, 2, HIGH - 1, HIGH, HIGH + 1, MAX - 1, MAX];
+    for a in edges.iter().flat_map(|x| edges.iter().map(move |y| [*x, *y, *x, MAX])) {
+        for b in edges.iter().flat_map(|x| edges.iter().map(move |y| [*y, *x, *y])) {
+            let b = limbs(&b);
+            if !b.is_zero() {
+                check_div_rem(&limbs(&a), &b);
+            }
+        }
+    }
+
+    // smaller dividends and exact multiples
+    let b = hex("14da98f1d3099fdf5ab99254ae901e35c");
+    assert_eq!(BigUint::from_u64(7).div_rem(&b), (BigUint::zero(), BigUint::from_u64(7)));
+    assert_eq!((&b * &b).div_rem(&b), (b.clone(), BigUint::zero()));
+    assert_eq!(b.div_rem(&b), (BigUint::one(), BigUint::zero()));
+}
+
+#[test]
+#[should_panic(expected = "attempt to divide by zero")]
+fn div_rem_by_zero_panics() {
+    BigUint::from_u64(1).div_rem(&BigUint::zero());
+}
+
+#[test]
+fn carries_and_borrows_cross_limb_boundaries() {
+    let one = BigUint::one();
+    assert_eq!(&limbs(&[MAX]) + &one, limbs(&[0, 1]));
+    assert_eq!(&limbs(&[MAX, MAX, MAX]) + &one, limbs(&[0, 0, 0, 1]));
+    assert_eq!(&limbs(&[MAX, MAX]) + &limbs(&[MAX, MAX]), limbs(&[MAX - 1, MAX, 1]));
+    assert_eq!(&one + &limbs(&[MAX, MAX]), limbs(&[0, 0, 1]));
+
+    assert_eq!(&limbs(&[0, 1]) - &one, limbs(&[MAX]));
+    assert_eq!(&limbs(&[0, 0, 0, 1]) - &one, limbs(&[MAX, MAX, MAX]));
+    assert_eq!(&limbs(&[0, 0, 1]) - &limbs(&[1, 1]), limbs(&[MAX, MAX - 1]));
+    assert_eq!(&limbs(&[5, 7]) - &limbs(&[5, 7]), BigUint::zero());
+
+    // (2^64 - 1)^2 = 2^128 - 2^65 + 1 and (2^128 - 1)^2 = 2^256 - 2^129 + 1
+    assert_eq!(&limbs(&[MAX]) * &limbs(&[MAX]), limbs(&[1, MAX - 1]));
+    assert_eq!(&limbs(&[MAX, MAX]) * &limbs(&[MAX, MAX]), limbs(&[1, 0, MAX - 1, MAX]));
+    assert_eq!(&limbs(&[MAX, MAX]) * &BigUint::zero(), BigUint::zero());
+    assert_eq!(
+        &hex("65b0492c4f539b21c95055455e8f9bddea5d12982e46e80fa489b0bca16f72f2bb83586fca7")
+            * &hex("20675f2b46108cc721754ef2904acecf5bb9188b80599e9090b20bb257e8454"),
+        hex("cdf18e09656e0db2f2be3312496423b5ccc227a6768d960310c50a34311fbd319588e0261fc8d3f7ea97083847d77e99f3c9e19205c011c9de8f0667445c89cfca7c302cc")
+    );
+
+    assert_eq!(&limbs(&[HIGH]) << 1, limbs(&[0, 1]));
+    assert_eq!(&one << 64, limbs(&[0, 1]));
+    assert_eq!(&one << 130, limbs(&[0, 0, 4]));
+    assert_eq!(&limbs(&[0, 1]) >> 1, limbs(&[HIGH]));
+    assert_eq!(&limbs(&[0, 0, 4]) >> 130, one);
+    assert_eq!(&limbs(&[MAX, MAX]) >> 128, BigUint::zero());
+}
+
+#[test]
+fn checked_sub_returns_none_on_underflow() {
+    let small = BigUint::from_u64(5);
+    assert_eq!(small.checked_sub(&BigUint::from_u64(6)), None);
+    assert_eq!(BigUint::zero().checked_sub(&BigUint::one()), None);
+    assert_eq!(limbs(&[MAX]).checked_sub(&limbs(&[0, 1])), None);
+    // equal top limbs, the lower limb decides
+    assert_eq!(limbs(&[1, 7]).checked_sub(&limbs(&[2, 7])), None);
+
+    assert_eq!(small.checked_sub(&small), Some(BigUint::zero()));
+    assert_eq!(limbs(&[0, 1]).checked_sub(&BigUint::one()), Some(limbs(&[MAX])));
+    assert_eq!(limbs(&[2, 7]).checked_sub(&limbs(&[1, 7])), Some(BigUint::one()));
+}
+
+#[test]
+fn hex_and_byte_encodings_round_trip() {
+    let mut rng = ChaCha20Rng::from_seed([2; 32]);
+    for bits in [1, 8, 63, 64, 65, 127, 128, 129, 1000] {
+        let n = random(&mut rng, bits);
+        assert_eq!(BigUint::from_bytes_be(&n.to_bytes_be()), n);
+        assert_eq!(BigUint::from_hex(&format!("{n:x}")).unwrap(), n);
+        assert_eq!(BigUint::from_hex(&format!("{n:X}")).unwrap(), n);
+    }
+
+    let n = hex("0102030405060708090a0b0c0d0e0f10");
+    assert_eq!(n.to_bytes_be(), (1..=16).collect::<Vec<u8>>());
+    assert_eq!(n.limbs(), [0x090a0b0c0d0e0f10, 0x0102030405060708]);
+    // leading zeros are accepted on input and dropped on output
+    assert_eq!(BigUint::from_bytes_be(&[0, 0, 0, 1, 2]).to_bytes_be(), [1, 2]);
+    assert_eq!(hex("0000000000000000000000ff").to_bytes_be(), [0xff]);
+    assert_eq!(hex("00000000000000000000000000000001").limbs(), [1]);
+    assert_eq!(limbs(&[MAX, 0, 0]).limbs(), [MAX]);
+    // whitespace is skipped, as in the RFC constants
+    assert_eq!(hex("FFFFFFFF FFFFFFFF\n  C90FDAA2"), hex("ffffffffffffffffc90fdaa2"));
+
+    assert!(BigUint::zero().to_bytes_be().is_empty());
+    assert_eq!(BigUint::from_bytes_be(&[]), BigUint::zero());
+    assert_eq!(BigUint::from_bytes_be(&[0, 0]), BigUint::zero());
+    assert_eq!(hex("0"), BigUint::zero());
+    assert_eq!(format!("{:x}", BigUint::zero()), "0");
+
+    assert_eq!(BigUint::from_hex(""), None);
+    assert_eq!(BigUint::from_hex("  "), None);
+    assert_eq!(BigUint::from_hex("12g4"), None);
+    assert_eq!(BigUint::from_hex("0x12"), None);
+}
+
+#[test]
+fn decimal_formatting_crosses_chunk_boundaries() {
+    assert_eq!(BigUint::zero().to_string(), "0");
+    assert_eq!(limbs(&[MAX]).to_string(), "18446744073709551615");
+    assert_eq!(limbs(&[0, 1]).to_string(), "18446744073709551616");
+    assert_eq!(hex("1d6329f1c35ca4bfabb9f5610000000000").to_string(), format!("1{}", "0".repeat(40)));
+}
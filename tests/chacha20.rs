@@ -0,0 +1,91 @@
+mod common;
+
+use diffie_hellman::{chacha20_block, generate_prime, ChaCha20Rng, Parameters, PrivateKey, Rng};
+
+use common::hex;
+
+#[test]
+fn block_function_rfc_8439_section_2_3_2() {
+    let key: [u8; 32] = std::array::from_fn(|i| i as u8);
+    let nonce: [u8; 12] = hex("000000090000004a00000000").try_into().unwrap();
+    let expected = hex(
+        "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e
+         d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e",
+    );
+    assert_eq!(chacha20_block(&key, 1, &nonce).to_vec(), expected);
+}
+
+/// RFC 8439 appendix A.1: key, nonce, block counter and keystream
+const KEYSTREAM_VECTORS: [(&str, &str, u32, &str); 5] = [
+    (
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        0,
+        "76b8e0ada0f13d90405d6ae55386bd28bdd219b8a08ded1aa836efcc8b770dc7
+         da41597c5157488d7724e03fb8d84a376a43b8f41518a11cc387b669b2ee6586",
+    ),
+    (
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        1,
+        "9f07e7be5551387a98ba977c732d080dcb0f29a048e3656912c6533e32ee7aed
+         29b721769ce64e43d57133b074d839d531ed1f28510afb45ace10a1f4b794d6f",
+    ),
+    (
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "000000000000000000000000",
+        1,
+        "3aeb5224ecf849929b9d828db1ced4dd832025e8018b8160b82284f3c949aa5a
+         8eca00bbb4a73bdad192b5c42f73f2fd4e273644c8b36125a64addeb006c13a0",
+    ),
+    (
+        "00ff000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        2,
+        "72d54dfbf12ec44b362692df94137f328fea8da73990265ec1bbbea1ae9af0ca
+         13b25aa26cb4a648cb9b9d1be65b2c0924a66c54d545ec1b7374f4872e99f096",
+    ),
+    (
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000002",
+        0,
+        "c2c64d378cd536374ae204b9ef933fcd1a8b2288b3dfa49672ab765b54ee27c7
+         8a970e0e955c14f3a88e741b97c286f75f8fc299e8148362fa198a39531bed6d",
+    ),
+];
+
+#[test]
+fn block_function_rfc_8439_appendix_a_1() {
+    for (key, nonce, counter, expected) in KEYSTREAM_VECTORS {
+        let key: [u8; 32] = hex(key).try_into().unwrap();
+        let nonce: [u8; 12] = hex(nonce).try_into().unwrap();
+        assert_eq!(chacha20_block(&key, counter, &nonce).to_vec(), hex(expected));
+    }
+}
+
+#[test]
+fn rng_output_is_the_zero_nonce_keystream() {
+    // the first two appendix A.1 vectors are consecutive blocks under the all zero key
+    let mut rng = ChaCha20Rng::from_seed([0; 32]);
+    let mut out = vec![0u8; 128];
+    // uneven reads must not skip or repeat any keystream bytes
+    let (first, rest) = out.split_at_mut(7);
+    rng.fill_bytes(first).unwrap();
+    let (second, third) = rest.split_at_mut(80);
+    rng.fill_bytes(second).unwrap();
+    rng.fill_bytes(third).unwrap();
+    let expected = [hex(KEYSTREAM_VECTORS[0].3), hex(KEYSTREAM_VECTORS[1].3)].concat();
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn seeded_generation_is_reproducible() {
+    let seed = [7u8; 32];
+    let prime = |seed| generate_prime(128, &mut ChaCha20Rng::from_seed(seed)).unwrap();
+    assert_eq!(prime(seed), prime(seed));
+
+    let params = Parameters::ffdhe2048();
+    let key = |seed| PrivateKey::generate(params.clone(), &mut ChaCha20Rng::from_seed(seed)).unwrap().public_key();
+    assert_eq!(key(seed), key(seed));
+    assert_ne!(key(seed), key([8u8; 32]));
+}
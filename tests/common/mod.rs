@@ -0,0 +1,49 @@
+//! helpers shared by the integration tests, each test crate uses only some of them
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+
+use diffie_hellman::{DHError, Rng};
+
+/// decode hex test vectors, ignoring the whitespace they are laid out with
+pub fn hex(s: &str) -> Vec<u8> {
+    let digits: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+        .collect()
+}
+
+/// the `DHError` behind a failed result, panicking if it succeeded or failed otherwise
+pub fn error_of<T>(result: Result<T, Box<dyn std::error::Error>>) -> DHError {
+    match result {
+        Ok(_) => panic!("expected an error"),
+        Err(err) => *err.downcast::<DHError>().unwrap(),
+    }
+}
+
+/// an `Rng` that hands out the given draws in order, each one filling a whole request,
+/// so tests can choose exactly which candidates key generation sees
+pub struct ScriptedRng {
+    draws: VecDeque<Vec<u8>>,
+}
+
+impl ScriptedRng {
+    pub fn new(draws: impl IntoIterator<Item = Vec<u8>>) -> Self {
+        Self { draws: draws.into_iter().collect() }
+    }
+
+    /// the number of draws not yet handed out
+    pub fn remaining(&self) -> usize {
+        self.draws.len()
+    }
+}
+
+impl Rng for ScriptedRng {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let draw = self.draws.pop_front().expect("ran out of scripted draws");
+        assert_eq!(draw.len(), dest.len(), "scripted draw of the wrong length");
+        dest.copy_from_slice(&draw);
+        Ok(())
+    }
+}
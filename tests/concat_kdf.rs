@@ -0,0 +1,83 @@
+mod common;
+
+use diffie_hellman::{concat_kdf, ChaCha20Rng, DHError, OtherInfo, Parameters, PrivateKey, Sha256, Sha512};
+
+use common::hex;
+
+const Z: &str = "52169af5c485dcc2321eb8d26d5efa21fb9b93c98e38412ee2484cf14f0d0d23";
+
+/// NIST CAVP single step KDF vector for SHA-256
+#[test]
+fn concat_kdf_nist_vector() {
+    let other_info = hex("a1b2c3d4e53728157e634612c12d6d5223e204aeea4341565369647bd184bcd246f72971f292badaa2fe4124612cba");
+    assert_eq!(
+        concat_kdf::<Sha256>(&hex(Z), &other_info, 16).unwrap(),
+        hex("1c3bc9e7c4547c5191c0d478cccaed55")
+    );
+}
+
+#[test]
+fn other_info_encoding() {
+    let info = OtherInfo::new(b"AES-256-GCM").with_party_u_info(b"Alice").with_party_v_info(b"Bob");
+    assert_eq!(
+        info.to_bytes(),
+        hex("0000000b4145532d3235362d47434d 00000005416c696365 00000003426f62")
+    );
+    assert_eq!(
+        info.clone().with_supp_pub_info(&256u32.to_be_bytes()).to_bytes(),
+        hex("0000000b4145532d3235362d47434d 00000005416c696365 00000003426f62 0000000400000100")
+    );
+    // empty party info is still length prefixed
+    assert_eq!(OtherInfo::new(b"").to_bytes(), vec![0; 12]);
+}
+
+/// outputs longer than one hash block, computed with ConcatKDFHash from Python's cryptography
+#[test]
+fn concat_kdf_multiple_blocks() {
+    let info = OtherInfo::new(b"AES-256-GCM").with_party_u_info(b"Alice").with_party_v_info(b"Bob");
+    assert_eq!(
+        concat_kdf::<Sha256>(&hex(Z), &info.to_bytes(), 80).unwrap(),
+        hex("61d151dc245524701bff2573325bd3c7cc0bc3c6f076a547bca8cae381377ca7
+             179858e03c934874194175ae0daa761e2ddee15d676a75ac7c1694010e0055db
+             1753ac4dfd2a484d7601f20ac4e3f348")
+    );
+    assert_eq!(
+        concat_kdf::<Sha512>(&hex(Z), &info.to_bytes(), 100).unwrap(),
+        hex("0023d53be172d4023a4fd7b9b53f3bde3fbe5bdbecbe62ce5cca3f9dccdf9e7c
+             bd5210c521e72feacbd1aaa4f159ee419df6ab4c4323397a1529c603b15442bd
+             f88f9773759091a1e459d851b6e883f7ba78ada5488344dff5769c5785017bc3
+             8e5c3325")
+    );
+    assert_eq!(concat_kdf::<Sha256>(&hex(Z), b"", 0).unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn both_parties_derive_the_same_key() {
+    let params = Parameters::ffdhe2048();
+    let mut rng = ChaCha20Rng::from_seed([22; 32]);
+    let alice = PrivateKey::generate(params.clone(), &mut rng).unwrap();
+    let bob = PrivateKey::generate(params, &mut rng).unwrap();
+    let a = alice.diffie_hellman(&bob.public_key()).unwrap();
+    let b = bob.diffie_hellman(&alice.public_key()).unwrap();
+
+    let info = OtherInfo::new(b"AES-256-GCM").with_party_u_info(b"Alice").with_party_v_info(b"Bob");
+    let key = a.derive_key_concat::<Sha256>(&info, 32).unwrap();
+    assert_eq!(key, b.derive_key_concat::<Sha256>(&info, 32).unwrap());
+
+    // swapping the roles changes the key
+    let swapped = OtherInfo::new(b"AES-256-GCM").with_party_u_info(b"Bob").with_party_v_info(b"Alice");
+    assert_ne!(key, a.derive_key_concat::<Sha256>(&swapped, 32).unwrap());
+
+    // Z is padded to the length of p
+    let mut z = a.value().to_bytes_be();
+    z.splice(0..0, vec![0; 256 - z.len()]);
+    assert_eq!(key, concat_kdf::<Sha256>(&z, &info.to_bytes(), 32).unwrap());
+}
+
+#[test]
+fn too_long_output_is_rejected() {
+    if usize::BITS > 32 {
+        let err = concat_kdf::<Sha256>(b"z", b"", (u32::MAX as usize + 1) * 32).unwrap_err();
+        assert!(matches!(err.downcast_ref::<DHError>(), Some(DHError::DerivedKeyTooLong)));
+    }
+}
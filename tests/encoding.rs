@@ -0,0 +1,96 @@
+mod common;
+
+use diffie_hellman::{BigUint, ChaCha20Rng, DHError, Parameters, PrivateKey, PublicKey, SharedSecret};
+
+use common::error_of;
+
+#[test]
+fn public_keys_are_padded_to_the_length_of_p() {
+    let params = Parameters::ffdhe2048();
+    let small = PublicKey::new(params.clone(), BigUint::from_u64(0x0102));
+    let bytes = small.to_bytes_be();
+    assert_eq!(bytes.len(), 256);
+    assert!(bytes[..254].iter().all(|b| *b == 0));
+    assert_eq!(bytes[254..], [1, 2]);
+    assert_eq!(PublicKey::from_bytes_be(params.clone(), &bytes).unwrap(), small);
+
+    let mut rng = ChaCha20Rng::from_seed([23; 32]);
+    let key = PrivateKey::generate(params.clone(), &mut rng).unwrap().public_key();
+    assert_eq!(PublicKey::from_bytes_be(params, &key.to_bytes_be()).unwrap(), key);
+}
+
+#[test]
+fn short_inputs_are_left_padded() {
+    let params = Parameters::ffdhe2048();
+    let key = PublicKey::from_bytes_be(params, &[1, 2]).unwrap();
+    assert_eq!(*key.value(), BigUint::from_u64(0x0102));
+}
+
+#[test]
+fn overlong_and_unreduced_inputs_are_rejected() {
+    let params = Parameters::ffdhe2048();
+    let p = params.p().to_bytes_be();
+
+    // one leading zero too many is rejected even though the value would fit
+    let mut long = vec![0u8; 257];
+    long[256] = 2;
+    assert!(matches!(error_of(PublicKey::from_bytes_be(params.clone(), &long)), DHError::EncodingTooLong));
+
+    assert!(matches!(error_of(PublicKey::from_bytes_be(params.clone(), &p)), DHError::NotReduced));
+    assert!(matches!(
+        error_of(SharedSecret::from_bytes_be(params.clone(), &[0xff; 256])),
+        DHError::NotReduced
+    ));
+
+    let below = (params.p() - &BigUint::one()).to_bytes_be();
+    assert!(PublicKey::from_bytes_be(params, &below).is_ok());
+}
+
+#[test]
+fn shared_secrets_round_trip() {
+    let params = Parameters::ffdhe2048();
+    let mut rng = ChaCha20Rng::from_seed([23; 32]);
+    let alice = PrivateKey::generate(params.clone(), &mut rng).unwrap();
+    let bob = PrivateKey::generate(params.clone(), &mut rng).unwrap();
+
+    // the peer's public value travels as bytes
+    let received = PublicKey::from_bytes_be(params.clone(), &bob.public_key().to_bytes_be()).unwrap();
+    let secret = alice.diffie_hellman(&received).unwrap();
+    let bytes = secret.to_bytes_be();
+    assert_eq!(bytes.len(), 256);
+    assert_eq!(BigUint::from_bytes_be(&bytes), *secret.value());
+
+    let restored = SharedSecret::from_bytes_be(params, &bytes).unwrap();
+    assert!(restored == secret);
+    assert_eq!(restored.to_bytes_be(), bytes);
+}
+
+#[test]
+fn encodings_span_partial_limbs() {
+    // a 141 bit p takes 18 bytes, two full limbs and two bytes of a third
+    let params = Parameters::new(BigUint::from_hex("105f994c09c7787ac0f0145bb715c95b399b").unwrap(), BigUint::from_u64(2));
+    let y = BigUint::from_hex("102030405060708090a0b0c0d0e0f1011").unwrap();
+    let bytes = PublicKey::new(params.clone(), y.clone()).to_bytes_be();
+    assert_eq!(bytes.len(), 18);
+    assert_eq!(bytes[0], 0);
+    assert_eq!(BigUint::from_bytes_be(&bytes), y);
+
+    let tiny = Parameters::new(BigUint::from_u64(23), BigUint::from_u64(5));
+    assert_eq!(PublicKey::new(tiny.clone(), BigUint::from_u64(22)).to_bytes_be(), [22]);
+    assert_eq!(SharedSecret::from_bytes_be(tiny, &[]).unwrap().to_bytes_be(), [0]);
+}
+
+#[test]
+fn secrets_are_redacted_in_debug_output() {
+    let params = Parameters::ffdhe2048();
+    let alice = PrivateKey::new(params.clone(), BigUint::from_u64(0xdeadbeef)).unwrap();
+    let bob = PrivateKey::new(params, BigUint::from_u64(0xfeedface)).unwrap();
+    let secret = alice.diffie_hellman(&bob.public_key()).unwrap();
+
+    let printed = format!("{alice:?}");
+    assert!(printed.contains("<redacted>"));
+    assert!(!printed.contains("deadbeef"));
+    let printed = format!("{secret:?}");
+    assert!(printed.contains("<redacted>"));
+    assert!(!printed.contains(&format!("{:x}", secret.value())));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::error_of;
+use diffie_hellman::{baillie_psw, generate_prime, generate_safe_prime, BigUint, ChaCha20Rng, DHError, Parameters, PrivateKey};
+
+#[test]
+fn generated_primes_have_the_exact_bit_length() {
+    let mut rng = ChaCha20Rng::from_seed([8; 32]);
+    // below and above the size where generation switches to sieving
+    for bits in [2, 3, 8, 15, 16, 17, 64, 65, 128, 256] {
+        for _ in 0..4 {
+            let p = generate_prime(bits, &mut rng).unwrap();
+            assert_eq!(p.bits(), bits);
+            assert!(baillie_psw(&p), "{p}");
+        }
+    }
+}
+
+#[test]
+fn generated_safe_primes_have_a_prime_half() {
+    let mut rng = ChaCha20Rng::from_seed([9; 32]);
+    for bits in [3, 5, 12, 16, 17, 64, 128, 256, 512] {
+        let p = generate_safe_prime(bits, &mut rng).unwrap();
+        assert_eq!(p.bits(), bits);
+        assert!(baillie_psw(&p), "{p}");
+        let q = &(&p - &BigUint::one()) >> 1;
+        assert!(baillie_psw(&q), "{p}");
+    }
+}
+
+#[test]
+fn generation_is_reproducible_from_a_seed() {
+    let a = generate_safe_prime(128, &mut ChaCha20Rng::from_seed([10; 32])).unwrap();
+    let b = generate_safe_prime(128, &mut ChaCha20Rng::from_seed([10; 32])).unwrap();
+    let c = generate_safe_prime(128, &mut ChaCha20Rng::from_seed([11; 32])).unwrap();
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+}
+
+#[test]
+fn too_few_bits_are_rejected() {
+    let mut rng = ChaCha20Rng::from_seed([12; 32]);
+    for bits in [0, 1] {
+        assert!(matches!(error_of(generate_prime(bits, &mut rng)), DHError::InvalidBitLength));
+    }
+    for bits in [0, 1, 2] {
+        assert!(matches!(error_of(generate_safe_prime(bits, &mut rng)), DHError::InvalidBitLength));
+    }
+}
+
+#[test]
+fn generated_parameters_are_valid() {
+    let mut rng = ChaCha20Rng::from_seed([13; 32]);
+    for bits in [32, 128, 256] {
+        let params = Parameters::generate(bits, &mut rng).unwrap();
+        assert_eq!(params.p().bits(), bits);
+        params.is_valid().unwrap();
+        assert_eq!(params.is_valid_safe_prime().unwrap(), params.p() >> 1);
+
+        let a = PrivateKey::generate(params.clone(), &mut rng).unwrap();
+        let b = PrivateKey::generate(params, &mut rng).unwrap();
+        let ab = a.diffie_hellman(&b.public_key()).unwrap();
+        let ba = b.diffie_hellman(&a.public_key()).unwrap();
+        assert_eq!(ab.to_bytes_be(), ba.to_bytes_be());
+    }
+}
+
+#[test]
+fn primes_that_are_not_safe_are_rejected() {
+    // 29 = 2 * 14 + 1 and 13 = 2 * 6 + 1 are prime but their halves are not
+    for p in [29, 13, 17, 41, 97] {
+        let p = BigUint::from_u64(p);
+        assert!(matches!(error_of(Parameters::is_safe_prime(&p)), DHError::NotSafePrime), "{p}");
+    }
+    // composites fail the primality check on p first
+    assert!(matches!(error_of(Parameters::is_safe_prime(&BigUint::from_u64(15))), DHError::InvalidP));
+    for (p, q) in [(5, 2), (7, 3), (11, 5), (23, 11), (2039, 1019)] {
+        assert_eq!(Parameters::is_safe_prime(&BigUint::from_u64(p)).unwrap(), BigUint::from_u64(q));
+    }
+
+    // a generated prime that is not safe, checked past 64 bits
+    let mut rng = ChaCha20Rng::from_seed([14; 32]);
+    let p = (0..)
+        .map(|_| generate_prime(256, &mut rng).unwrap())
+        .find(|p| !baillie_psw(&(p >> 1)))
+        .unwrap();
+    assert!(matches!(error_of(Parameters::is_safe_prime(&p)), DHError::NotSafePrime));
+    let params = Parameters::new(p, BigUint::from_u64(2));
+    assert!(matches!(error_of(params.is_valid_safe_prime()), DHError::NotSafePrime));
+}
+
+#[test]
+fn safe_prime_validation_reports_q_and_checks_g() {
+    let p = generate_safe_prime(256, &mut ChaCha20Rng::from_seed([15; 32])).unwrap();
+    let q = &p >> 1;
+    for g in [BigUint::from_u64(2), BigUint::from_u64(4), &p - &BigUint::from_u64(2)] {
+        assert_eq!(Parameters::new(p.clone(), g).is_valid_safe_prime().unwrap(), q);
+    }
+    for g in [BigUint::zero(), BigUint::one(), &p - &BigUint::one(), p.clone()] {
+        let params = Parameters::new(p.clone(), g);
+        assert!(matches!(error_of(params.is_valid_safe_prime()), DHError::InvalidG));
+    }
+}
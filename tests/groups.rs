@@ -0,0 +1,260 @@
+use diffie_hellman::{BigUint, NamedGroup, Parameters, PrimalityPolicy, PrivateKey};
+
+/// floor(pi * 2^bits), from Machin's formula pi = 16 arctan(1/5) - 4 arctan(1/239)
+fn pi_scaled(bits: usize) -> BigUint {
+    const GUARD: usize = 64;
+    let one = &BigUint::one() << (bits + GUARD);
+    let (pos_5, neg_5) = arctan_inv(&one, 5);
+    let (pos_239, neg_239) = arctan_inv(&one, 239);
+    let sixteen = BigUint::from_u64(16);
+    let four = BigUint::from_u64(4);
+    let pi = &(&(&sixteen * &pos_5) + &(&four * &neg_239)) - &(&(&sixteen * &neg_5) + &(&four * &pos_239));
+    &pi >> GUARD
+}
+
+/// arctan(1/x) * one as its positive and negative series terms
+fn arctan_inv(one: &BigUint, x: u64) -> (BigUint, BigUint) {
+    let mut pos = BigUint::zero();
+    let mut neg = BigUint::zero();
+    let mut power = one.div_rem_u64(x).0;
+    let mut n = 1;
+    while !power.is_zero() {
+        let term = power.div_rem_u64(n).0;
+        match n % 4 {
+            1 => pos = &pos + &term,
+            _ => neg = &neg + &term,
+        }
+        power = power.div_rem_u64(x * x).0;
+        n += 2;
+    }
+    (pos, neg)
+}
+
+/// the RFC 3526 construction p = 2^n - 2^(n-64) - 1 + 2^64 * ([2^(n-130) pi] + k)
+fn modp_prime(n: usize, k: u64) -> BigUint {
+    let one = BigUint::one();
+    let top = &(&(&one << n) - &(&one << (n - 64))) - &one;
+    let middle = &pi_scaled(n - 130) + &BigUint::from_u64(k);
+    &top + &(&middle << 64)
+}
+
+#[test]
+fn modp_primes_match_rfc_3526_construction() {
+    let groups = [
+        (Parameters::modp_2048(), 2048, 124476),
+        (Parameters::modp_3072(), 3072, 1690314),
+        (Parameters::modp_4096(), 4096, 240904),
+        (Parameters::modp_6144(), 6144, 929484),
+        (Parameters::modp_8192(), 8192, 4743158),
+    ];
+    for (group, bits, k) in groups {
+        assert_eq!(group.p().bits(), bits);
+        assert_eq!(*group.p(), modp_prime(bits, k), "modp_{bits}");
+        assert_eq!(*group.g(), BigUint::from_u64(2));
+    }
+}
+
+#[test]
+fn modp_2048_matches_rfc_3526_text() {
+    let group = Parameters::modp_2048();
+    let hex = format!("{:X}", group.p());
+    assert!(hex.starts_with("FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD1"));
+    assert!(hex.ends_with("15728E5A8AACAA68FFFFFFFFFFFFFFFF"));
+}
+
+#[test]
+fn modp_2048_is_a_safe_prime_group() {
+    let group = Parameters::modp_2048();
+    let q = group.is_valid_safe_prime_with(PrimalityPolicy::MillerRabin(1)).unwrap();
+    assert_eq!(&(&q << 1) + &BigUint::one(), *group.p());
+}
+
+/// floor(e * 2^bits), from the series e = sum 1/n!
+fn e_scaled(bits: usize) -> BigUint {
+    const GUARD: usize = 64;
+    let mut term = &BigUint::one() << (bits + GUARD);
+    let mut sum = BigUint::zero();
+    let mut n = 0;
+    while !term.is_zero() {
+        sum = &sum + &term;
+        n += 1;
+        term = term.div_rem_u64(n).0;
+    }
+    &sum >> GUARD
+}
+
+/// the RFC 7919 construction p = 2^b - 2^(b-64) + ([2^(b-130) e] + X) * 2^64 - 1
+fn ffdhe_prime(b: usize, x: u64) -> BigUint {
+    let one = BigUint::one();
+    let middle = &e_scaled(b - 130) + &BigUint::from_u64(x);
+    &(&(&(&one << b) - &(&one << (b - 64))) + &(&middle << 64)) - &one
+}
+
+#[test]
+fn ffdhe_primes_match_rfc_7919_construction() {
+    let groups = [
+        (Parameters::ffdhe2048(), 2048, 560316),
+        (Parameters::ffdhe3072(), 3072, 2625351),
+        (Parameters::ffdhe4096(), 4096, 5736041),
+        (Parameters::ffdhe6144(), 6144, 15705020),
+        (Parameters::ffdhe8192(), 8192, 10965728),
+    ];
+    for (group, bits, x) in groups {
+        assert_eq!(group.p().bits(), bits);
+        assert_eq!(*group.p(), ffdhe_prime(bits, x), "ffdhe{bits}");
+        assert_eq!(*group.g(), BigUint::from_u64(2));
+    }
+}
+
+#[test]
+fn named_groups_map_to_codepoints_and_back() {
+    for (codepoint, group) in (0x0100..=0x0104).zip(NamedGroup::ALL) {
+        assert_eq!(group.codepoint(), codepoint);
+        assert_eq!(NamedGroup::from_codepoint(codepoint), Some(group));
+        assert_eq!(group.parameters().named_group(), Some(group));
+    }
+    assert_eq!(NamedGroup::from_codepoint(0x001d), None);
+    assert_eq!(Parameters::modp_2048().named_group(), None);
+}
+
+/// a two party exchange with fixed private keys under one of the RFC 5114 groups
+struct ExchangeVector {
+    group: Parameters,
+    x_a: &'static str,
+    x_b: &'static str,
+    y_a: &'static str,
+    y_b: &'static str,
+    z: &'static str,
+}
+
+fn check_exchange(vector: ExchangeVector) {
+    let hex = |s| BigUint::from_hex(s).unwrap();
+    let alice = PrivateKey::new(vector.group.clone(), hex(vector.x_a)).unwrap();
+    let bob = PrivateKey::new(vector.group, hex(vector.x_b)).unwrap();
+
+    let (alice_public, bob_public) = (alice.public_key(), bob.public_key());
+    assert_eq!(*alice_public.value(), hex(vector.y_a));
+    assert_eq!(*bob_public.value(), hex(vector.y_b));
+    assert_eq!(*alice.diffie_hellman(&bob_public).unwrap().value(), hex(vector.z));
+    assert_eq!(*bob.diffie_hellman(&alice_public).unwrap().value(), hex(vector.z));
+}
+
+/// RFC 5114 appendix A.1
+#[test]
+fn rfc5114_1024_160_exchange() {
+    check_exchange(ExchangeVector {
+        group: Parameters::rfc5114_1024_160(),
+        x_a: "
+            B9A3B3AE 8FEFC1A2 93049650 7086F845 5D48943E",
+        x_b: "
+            9392C9F9 EB6A7A6A 9022F7D8 3E7223C6 835BBDDA",
+        y_a: "
+            2A853B3D 92197501 B9015B2D EB3ED84F 5E021DCC 3E52F109 D3273D2B 7521281C
+            BABE0E76 FF5727FA 8ACCE269 56BA9A1F CA26F202 28D8693F EB10841D 84A73600
+            54ECE5A7 F5B7A61A D3DFB3C6 0D2E4310 6D8727DA 37DF9CCE 95B47875 5D06BCEA
+            8F9D4596 5F75A5F3 D1DF3701 165FC9E5 0C4279CE B07F9895 40AE96D5 D88ED776",
+        y_b: "
+            717A6CB0 53371FF4 A3B93294 1C1E5663 F861A1D6 AD34AE66 576DFB98 F6C6CBF9
+            DDD5A56C 7833F6BC FDFF0955 82AD868E 440E8D09 FD769E3C ECCDC3D3 B1E4CFA0
+            57776CAA F9739B6A 9FEE8E74 11F8D6DA C09D6A4E DB46CC2B 5D520309 0EAE6126
+            311E53FD 2C14B574 E6A3109A 3DA1BE41 BDCEAA18 6F5CE067 16A2B6A0 7B3C33FE",
+        z: "
+            5C804F45 4D30D9C4 DF85271F 93528C91 DF6B48AB 5F80B3B5 9CAAC1B2 8F8ACBA9
+            CD3E39F3 CB614525 D9521D2E 644C53B8 07B810F3 40062F25 7D7D6FBF E8D5E8F0
+            72E9B6E9 AFDA9413 EAFB2E8B 0699B1FB 5A0CACED DEAEAD7E 9CFBB36A E2B42083
+            5BD83A19 FB0B5E96 BF8FA4D0 9E345525 167ECD91 55416F46 F408ED31 B63C6E6D",
+    });
+}
+
+/// not the RFC 5114 appendix A.2 vector: the private keys are our own and the public values
+/// and shared secret were computed by OpenSSL, so this checks interoperability only
+/// TODO: replace with the xA, yA, xB, yB and Z of appendix A.2, as done for A.1 and A.3
+#[test]
+fn rfc5114_2048_224_exchange_matches_openssl() {
+    check_exchange(ExchangeVector {
+        group: Parameters::rfc5114_2048_224(),
+        x_a: "
+            647EACF1 48A3F41D 38ACD40A 9FD3F4C3 8AC78FAB 21231C55 EFE54C91",
+        x_b: "
+            7B73AD13 E8897197 EF74C663 FE689222 DE68AF0B 0D6B995A 0198B66B",
+        y_a: "
+            3EECE7F4 90206A3E 31F97858 3A95DD5A DCE79D22 BB4667CB 253A996A 53B09B84
+            77B9BED1 4A33D190 5B60DE8C 38BA45D2 628ED1AF EFFF15BA 8D72A1A1 A2937B81
+            3AEA6E04 0B80B980 C27AD3D0 EC374899 679835ED 51FD337C 2527DDCC 450C1E12
+            5ED13CD2 663B153B 6BB7D6B0 682FF057 33FCA29C 9D63C5FF F118593B E1C07198
+            D3637C2C 950E8EB7 4C62B71A F89BCE88 03E73348 FE3494D1 396C8FC2 6C4EC765
+            D3FE0E72 C8A54A13 CE8B6473 CA15F522 1343CF7B 9B02004A 0A990D45 213E96E7
+            BD8FFEF5 8C8EFA8D A07AAC07 14C759C3 AA463932 51FB77BA CBFE0D80 86DCF0C9
+            D6EE55BA 627A95A6 31C4BA8B DF215E5A 84664B4A AFA0D17C 41ABDC9F 8192C003",
+        y_b: "
+            A671B565 D2F7C62C F08B8123 B5999984 4AEBAD6D 31E86B35 590B83F6 53E9E7A0
+            07FF72EE B366E80B C4591925 6D252FD3 6ACCEC32 31CF9870 EB790C3F 742CA568
+            23D1FF7E 1ABC30D0 D2BAA1E9 57C1FC05 AA19F7C0 D303D169 1037E96D 7670C6FC
+            4CEC8DB1 66C10079 98957216 03DDA0F6 E188096B 30DD9410 948D273D 89A335BF
+            03336075 AE93E916 D19F827B C99DDB4A 19AA7667 5981D1BC F7109683 92B45ADA
+            891ECB5B EF9607F0 75173480 FBD380CD CBF89A88 89D20F49 4B4E4E61 C2C193FE
+            9E4147E0 43361338 F1AB3B3F 5C38CD08 B5DB25C9 956C1503 3CC50B07 5973FE35
+            EF69DA81 745900AA AFA95EDD 251B50CC F35E80AC 414B6D36 521D0D37 69ADFD1B",
+        z: "
+            62EB63C4 CB427D23 89F0F8BE CD966395 034ADA54 B402669B 04101DD0 4AA3D3FC
+            AA161C2A 2CC3B736 78AE83C0 161646DB C2084FF9 75DBAA84 9965569E 169D2308
+            2401BC7A 41AF2641 D1B2E63C C45E7090 04FA45CE 46A3CCF7 59E6AB93 FE16623C
+            AB7C4F58 3761B154 13954B25 F5F9094F C505E096 A732FA5B CA426FFF A2CAE849
+            B8ABF095 D6726A83 0F3461E8 465CEA02 5C828273 7C792DDC 1A2B0259 19D5558A
+            E718F235 27023B67 5107F925 F48733FF A037E3D3 E5BF3577 68C52305 4F48448C
+            C3B3A54E F9239D80 136A258B B546942A 59BCA102 4D8A7F18 DA358D06 30DE5D4F
+            10D3BC29 DACE67DE EADF302E D0C24F13 AEE74387 B7DD0900 CB1A52A0 6C177E62",
+    });
+}
+
+/// RFC 5114 appendix A.3
+#[test]
+fn rfc5114_2048_256_exchange() {
+    check_exchange(ExchangeVector {
+        group: Parameters::rfc5114_2048_256(),
+        x_a: "
+            0881382C DB87660C 6DC13E61 4938D5B9 C8B2F248 581CC5E3 1B354543 97FCE50E",
+        x_b: "
+            7D62A7E3 EF36DE61 7B13D1AF B82C780D 83A23BD4 EE670564 5121F371 F546A53D",
+        y_a: "
+            2E9380C8 323AF975 45BC4941 DEB0EC37 42C62FE0 ECE824A6 ABDBE66C 59BEE024
+            2911BFB9 67235CEB A35AE13E 4EC752BE 630B92DC 4BDE2847 A9C62CB8 15274542
+            1FB7EB60 A63C0FE9 159FCCE7 26CE7CD8 523D7450 667EF840 E4919121 EB5F01C8
+            C9B0D3D6 48A93BFB 75689E82 44AC134A F544711C E79A02DC C3422668 4780DDDC
+            B4985941 06C37F5B C7985648 7AF5AB02 2A2E5E42 F09897C1 A85A11EA 0212AF04
+            D9B4CEBC 937C3C1A 3E15A8A0 342E3376 15C84E7F E3B8B9B8 7FB1E73A 15AF12A3
+            0D746E06 DFC34F29 0D797CE5 1AA13AA7 85BF6658 AFF5E4B0 93003CBE AF665B3C
+            2E113A3A 4E905269 341DC071 1426685F 4EF37E86 8A8126FF 3F2279B5 7CA67E29",
+        y_b: "
+            575F0351 BD2B1B81 7448BDF8 7A6C362C 1E289D39 03A30B98 32C5741F A250363E
+            7ACBC7F7 7F3DACBC 1F131ADD 8E03367E FF8FBBB3 E1C57844 24809B25 AFE4D226
+            2A1A6FD2 FAB64105 CA30A674 E07F7809 85208863 2FC04923 3791AD4E DD083A97
+            8B883EE6 18BC5E0D D047415F 2D95E683 CF14826B 5FBE10D3 CE41C6C1 20C78AB2
+            0008C698 BF7F0BCA B9D7F407 BED0F43A FB2970F5 7F8D1204 3963E66D DD320D59
+            9AD9936C 8F44137C 08B180EC 5E985CEB E186F3D5 49677E80 607331EE 17AF3380
+            A725B078 2317D7DD 43F59D7A F9568A9B B63A84D3 65F92244 ED120988 219302F4
+            2924C7CA 90B89D24 F71B0AB6 97823D7D EB1AFF5B 0E8E4A45 D49F7F53 757E1913",
+        z: "
+            86C70BF8 D0BB81BB 01078A17 219CB7D2 7203DB2A 19C877F1 D1F19FD7 D77EF225
+            46A68F00 5AD52DC8 4553B78F C60330BE 51EA7C06 72CAC151 5E4B35C0 47B9A551
+            B88F39DC 26DA14A0 9EF74774 D47C762D D177F9ED 5BC2F11E 52C879BD 95098504
+            CD9EECD8 A8F9B3EF BD1F008A C5853097 D9D1837F 2B18F77C D7BE01AF 80A7C7B5
+            EA3CA54C C02D0C11 6FEE3F95 BB873993 85875D7E 86747E67 6E728938 ACBFF709
+            8E05BE4D CFB24052 B83AEFFB 14783F02 9ADBDE7F 53FAE920 84224090 E007CEE9
+            4D4BF2BA CE9FFD4B 57D2AF7C 724D0CAA 19BF0501 F6F17B4A A10F425E 3EA76080
+            B4B9D6B3 CEFEA115 B2CEB878 9BB8A3B0 EA87FEBE 63B6C8F8 46EC6DB0 C26C5D7C",
+    });
+}
+#[test]
+fn rfc5114_groups_generate_their_subgroups() {
+    let groups = [
+        (Parameters::rfc5114_1024_160(), 1024, 160),
+        (Parameters::rfc5114_2048_224(), 2048, 224),
+        (Parameters::rfc5114_2048_256(), 2048, 256),
+    ];
+    for (group, p_bits, q_bits) in groups {
+        assert_eq!(group.p().bits(), p_bits);
+        assert_eq!(group.q().unwrap().bits(), q_bits);
+        group.is_valid_with(PrimalityPolicy::MillerRabin(1)).unwrap();
+    }
+}
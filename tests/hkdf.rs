@@ -0,0 +1,242 @@
+mod common;
+
+use diffie_hellman::{
+    hkdf, hkdf_expand, hkdf_extract, hmac, sha256, sha384, sha512, ChaCha20Rng, DHError, Hash, Parameters, PrivateKey,
+    Sha256, Sha384, Sha512,
+};
+
+use common::hex;
+
+/// messages from the FIPS 180-2 examples, plus the empty message
+fn messages() -> [Vec<u8>; 4] {
+    [
+        b"abc".to_vec(),
+        b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".to_vec(),
+        vec![b'a'; 1_000_000],
+        Vec::new(),
+    ]
+}
+
+/// the 896 bit two block example used for SHA-384 and SHA-512 in place of the 448 bit one
+const LONG_MESSAGE_512: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+fn check_digests<H: Hash>(messages: &[Vec<u8>], expected: &[&str]) {
+    for (message, digest) in messages.iter().zip(expected) {
+        assert_eq!(H::digest(message), hex(digest));
+        assert_eq!(H::digest(message).len(), H::OUTPUT_LEN);
+    }
+}
+
+#[test]
+fn sha256_fips_180_2_examples() {
+    check_digests::<Sha256>(
+        &messages(),
+        &[
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ],
+    );
+    assert_eq!(sha256(b"abc"), Sha256::digest(b"abc"));
+}
+
+#[test]
+fn sha384_fips_180_2_examples() {
+    let mut messages = messages();
+    messages[1] = LONG_MESSAGE_512.to_vec();
+    check_digests::<Sha384>(
+        &messages,
+        &[
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+            "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039",
+            "9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985",
+            "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b",
+        ],
+    );
+    assert_eq!(sha384(b"abc"), Sha384::digest(b"abc"));
+}
+
+#[test]
+fn sha512_fips_180_2_examples() {
+    let mut messages = messages();
+    messages[1] = LONG_MESSAGE_512.to_vec();
+    check_digests::<Sha512>(
+        &messages,
+        &[
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018
+             501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909",
+            "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb
+             de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b",
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+        ],
+    );
+    assert_eq!(sha512(b"abc"), Sha512::digest(b"abc"));
+}
+
+fn check_incremental<H: Hash>() {
+    let data: Vec<u8> = (0..1000u32).map(|i| (i * 31) as u8).collect();
+    for split in [0, 1, 55, 56, 63, 64, 65, 111, 112, 127, 128, 129, 999] {
+        let mut hasher = H::new();
+        let (a, b) = data.split_at(split);
+        for chunk in a.chunks(7) {
+            hasher.update(chunk);
+        }
+        hasher.update(b);
+        assert_eq!(hasher.finalize(), H::digest(&data));
+    }
+}
+
+#[test]
+fn incremental_hashing_matches_one_shot() {
+    check_incremental::<Sha256>();
+    check_incremental::<Sha384>();
+    check_incremental::<Sha512>();
+}
+
+/// RFC 4231 test cases 1, 2 and 6: key, data, then HMAC-SHA256, HMAC-SHA384 and HMAC-SHA512
+const HMAC_VECTORS: [(&str, &str, &str, &str, &str); 3] = [
+    (
+        "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+        "4869205468657265",
+        "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+        "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec682aa034c7cebc59c
+         faea9ea9076ede7f4af152e8b2fa9cb6",
+        "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde
+         daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+    ),
+    (
+        "4a656665",
+        "7768617420646f2079612077616e7420666f72206e6f7468696e673f",
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e
+         8e2240ca5e69e2c78b3239ecfab21649",
+        "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554
+         9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+    ),
+    (
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
+         aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
+         aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
+         aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
+         aaaaaa",
+        "54657374205573696e67204c6172676572205468616e20426c6f636b2d53697a
+         65204b6579202d2048617368204b6579204669727374",
+        "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+        "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f3cd11f05033ac4c6
+         0c2ef6ab4030fe8296248df163f44952",
+        "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352
+         6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+    ),
+];
+
+#[test]
+fn hmac_rfc_4231() {
+    for (key, data, sha256, sha384, sha512) in HMAC_VECTORS {
+        let (key, data) = (hex(key), hex(data));
+        assert_eq!(hmac::<Sha256>(&key, &data), hex(sha256));
+        assert_eq!(hmac::<Sha384>(&key, &data), hex(sha384));
+        assert_eq!(hmac::<Sha512>(&key, &data), hex(sha512));
+    }
+}
+
+/// RFC 5869 appendix A.1 to A.3: IKM, salt, info, PRK and OKM
+const HKDF_VECTORS: [(&str, &str, &str, &str, &str); 3] = [
+    (
+        "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+        "000102030405060708090a0b0c",
+        "f0f1f2f3f4f5f6f7f8f9",
+        "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+        "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf
+         34007208d5b887185865",
+    ),
+    (
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f
+         202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f
+         404142434445464748494a4b4c4d4e4f",
+        "606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f
+         808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f
+         a0a1a2a3a4a5a6a7a8a9aaabacadaeaf",
+        "b0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecf
+         d0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeef
+         f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+        "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+        "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c
+         59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71
+         cc30c58179ec3e87c14c01d5c1f3434f1d87",
+    ),
+    (
+        "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+        "",
+        "",
+        "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+        "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d
+         9d201395faa4b61a96c8",
+    ),
+];
+
+#[test]
+fn hkdf_rfc_5869_test_vectors() {
+    for (ikm, salt, info, prk, okm) in HKDF_VECTORS {
+        let (ikm, salt, info, okm) = (hex(ikm), hex(salt), hex(info), hex(okm));
+        assert_eq!(hkdf_extract::<Sha256>(&salt, &ikm), hex(prk));
+        assert_eq!(hkdf_expand::<Sha256>(&hex(prk), &info, okm.len()).unwrap(), okm);
+        assert_eq!(hkdf::<Sha256>(&salt, &ikm, &info, okm.len()).unwrap(), okm);
+    }
+}
+
+/// RFC 5869 only covers SHA-256 and SHA-1, these outputs come from Python's hmac module
+#[test]
+fn hkdf_with_sha384_and_sha512() {
+    let ikm: Vec<u8> = (0..40).collect();
+    assert_eq!(
+        hkdf::<Sha384>(b"salt", &ikm, b"info", 100).unwrap(),
+        hex("c3c85a320896c7ff47ac56cf47dccc33a7d4d759a257293ff87bb01252defbce
+             42dca9400134bad9f949e6450c3034ba7414290624cc2f99a52fc3b8fff8250a
+             1967f1a88835e9514403dbc6ed240c6d284ead6cbd69ee72194ecc85c8018401
+             e94470a6")
+    );
+    assert_eq!(
+        hkdf::<Sha512>(b"salt", &ikm, b"info", 100).unwrap(),
+        hex("9884a7b425cf50d0c8cbdf4dce64c8dc8dccd21d47a18c0de3cfc50a5eac7451
+             c7fea6eb08185f1f315fc72a4d731a42a68aa7add4a36e5892c4baad06e1149a
+             3357e3ce56fab64b1266596141f002efeef52925175b8865277565bba5183ee6
+             dee79d20")
+    );
+}
+
+#[test]
+fn hkdf_expand_length_limit() {
+    let prk = [1u8; 64];
+    assert_eq!(hkdf_expand::<Sha256>(&prk, b"", 255 * 32).unwrap().len(), 255 * 32);
+    let err = hkdf_expand::<Sha256>(&prk, b"", 255 * 32 + 1).unwrap_err();
+    assert!(matches!(err.downcast_ref::<DHError>(), Some(DHError::DerivedKeyTooLong)));
+    assert_eq!(hkdf_expand::<Sha512>(&prk, b"", 255 * 64).unwrap().len(), 255 * 64);
+}
+
+#[test]
+fn both_parties_derive_the_same_key() {
+    let params = Parameters::ffdhe2048();
+    let mut rng = ChaCha20Rng::from_seed([20; 32]);
+    let alice = PrivateKey::generate(params.clone(), &mut rng).unwrap();
+    let bob = PrivateKey::generate(params, &mut rng).unwrap();
+    let a = alice.diffie_hellman(&bob.public_key()).unwrap();
+    let b = bob.diffie_hellman(&alice.public_key()).unwrap();
+
+    let key = a.derive_key::<Sha256>(b"salt", b"aead key", 32).unwrap();
+    assert_eq!(key.len(), 32);
+    assert_eq!(key, b.derive_key::<Sha256>(b"salt", b"aead key", 32).unwrap());
+    assert_ne!(key, a.derive_key::<Sha256>(b"salt", b"aead nonce", 32).unwrap());
+    assert_eq!(
+        a.derive_key::<Sha512>(b"salt", b"aead key", 64).unwrap(),
+        b.derive_key::<Sha512>(b"salt", b"aead key", 64).unwrap()
+    );
+
+    // z is padded to the length of p before hashing
+    let mut ikm = a.value().to_bytes_be();
+    ikm.splice(0..0, vec![0; 256 - ikm.len()]);
+    assert_eq!(key, hkdf::<Sha256>(b"salt", &ikm, b"aead key", 32).unwrap());
+}
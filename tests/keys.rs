@@ -0,0 +1,98 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use common::ScriptedRng;
+use diffie_hellman::{mod_pow, BigUint, ChaCha20Rng, Parameters, PrivateKey};
+
+/// p = 23 = 2 * 11 + 1, 5 is a primitive root and 2 generates the subgroup of order 11
+fn full_group() -> Parameters {
+    Parameters::new(BigUint::from_u64(23), BigUint::from_u64(5))
+}
+
+fn subgroup() -> Parameters {
+    Parameters::new(BigUint::from_u64(23), BigUint::from_u64(2)).with_subgroup_order(BigUint::from_u64(11))
+}
+
+/// the exponent behind a key over p = 23, found by search as it is unique below the order of g
+fn exponent_of(key: &PrivateKey) -> u64 {
+    let params = key.parameters();
+    let y = key.public_key().value().clone();
+    (0..22).find(|x| mod_pow(params.g(), &BigUint::from_u64(*x), params.p()) == y).unwrap()
+}
+
+fn ones(bits: usize) -> BigUint {
+    &(&BigUint::one() << bits) - &BigUint::one()
+}
+
+#[test]
+fn out_of_range_draws_are_rejected() {
+    // q - 2 = 9 takes 4 bits, so 0xf7 is masked down to 7 and 0xff to 15
+    let mut rng = ScriptedRng::new([0x00, 0x01, 0x0a, 0xff, 0xf7].map(|b| vec![b]));
+    let key = PrivateKey::generate(subgroup(), &mut rng).unwrap();
+    assert_eq!(rng.remaining(), 0);
+    assert_eq!(exponent_of(&key), 7);
+
+    // without q the range is [2, p - 3] = [2, 20], 5 bits, and its top is accepted
+    let mut rng = ScriptedRng::new([0x00, 0x01, 0x15, 0xff, 0xf4].map(|b| vec![b]));
+    let key = PrivateKey::generate(full_group(), &mut rng).unwrap();
+    assert_eq!(rng.remaining(), 0);
+    assert_eq!(exponent_of(&key), 20);
+
+    // the bottom of the range is accepted as well
+    let mut rng = ScriptedRng::new([vec![0x02]]);
+    assert_eq!(exponent_of(&PrivateKey::generate(subgroup(), &mut rng).unwrap()), 2);
+}
+
+#[test]
+fn generated_keys_cover_2_to_n_minus_2() {
+    let mut rng = ChaCha20Rng::from_seed([30; 32]);
+    for (params, highest) in [(subgroup(), 9), (full_group(), 20)] {
+        let seen: BTreeSet<u64> =
+            (0..300).map(|_| exponent_of(&PrivateKey::generate(params.clone(), &mut rng).unwrap())).collect();
+        assert_eq!(seen, (2..=highest).collect());
+    }
+}
+
+#[test]
+fn short_exponents_never_exceed_the_length() {
+    let mut rng = ChaCha20Rng::from_seed([31; 32]);
+    for params in [subgroup(), full_group()] {
+        let seen: BTreeSet<u64> = (0..200)
+            .map(|_| exponent_of(&PrivateKey::generate_with_length(params.clone(), 3, &mut rng).unwrap()))
+            .collect();
+        assert_eq!(seen, (2..=7).collect());
+    }
+
+    // a draw of all ones is masked to the largest exponent the length allows and accepted,
+    // with q known and without
+    let modp = Parameters::modp_2048();
+    let without_q = Parameters::new(modp.p().clone(), modp.g().clone());
+    for params in [Parameters::rfc5114_2048_256(), without_q] {
+        for bits in [160usize, 161, 225] {
+            let mut rng = ScriptedRng::new([vec![0xff; bits.div_ceil(8)]]);
+            let key = PrivateKey::generate_with_length(params.clone(), bits, &mut rng).unwrap();
+            assert_eq!(rng.remaining(), 0);
+            assert_eq!(*key.public_key().value(), mod_pow(params.g(), &ones(bits), params.p()), "{bits} bits");
+        }
+    }
+}
+
+#[test]
+fn lengths_past_the_order_fall_back_to_the_full_range() {
+    let modp = Parameters::modp_2048();
+    let without_q = Parameters::new(modp.p().clone(), modp.g().clone());
+    for (params, bits) in [(Parameters::rfc5114_2048_256(), 300), (without_q, 4096)] {
+        // the draw is as wide as n - 2, all ones lies past it and n - 2 itself is accepted
+        let n = match params.q() {
+            Some(q) => q.clone(),
+            None => params.p() - &BigUint::one(),
+        };
+        let highest = &n - &BigUint::from_u64(2);
+        let width = highest.to_bytes_be().len();
+        let mut rng = ScriptedRng::new([vec![0xff; width], highest.to_bytes_be()]);
+        let key = PrivateKey::generate_with_length(params.clone(), bits, &mut rng).unwrap();
+        assert_eq!(rng.remaining(), 0);
+        assert_eq!(*key.public_key().value(), mod_pow(params.g(), &highest, params.p()));
+    }
+}
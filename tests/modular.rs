@@ -0,0 +1,112 @@
+use diffie_hellman::{mod_pow, mod_pow_ct, BigUint, ChaCha20Rng, DHError, MontgomeryContext, Parameters, PrivateKey, Rng};
+
+fn random(rng: &mut ChaCha20Rng, limbs: usize) -> BigUint {
+    BigUint::from_limbs((0..limbs).map(|_| rng.next_u64().unwrap()).collect())
+}
+
+fn random_odd(rng: &mut ChaCha20Rng, limbs: usize) -> BigUint {
+    let n = random(rng, limbs);
+    match n.is_odd() {
+        true => n,
+        false => &n + &BigUint::one(),
+    }
+}
+
+/// square and multiply with plain division, independent of the Montgomery code under test
+fn reference_pow(base: &BigUint, exp: &BigUint, modulus: &BigUint) -> BigUint {
+    let base = base % modulus;
+    let mut res = &BigUint::one() % modulus;
+    for i in (0..exp.bits()).rev() {
+        res = &(&res * &res) % modulus;
+        if exp.bit(i) {
+            res = &(&res * &base) % modulus;
+        }
+    }
+    res
+}
+
+#[test]
+fn constant_time_pow_matches_mod_pow() {
+    let mut rng = ChaCha20Rng::from_seed([4; 32]);
+    for modulus_limbs in [1, 2, 3, 8] {
+        for exp_limbs in [0, 1, modulus_limbs, modulus_limbs + 3] {
+            let modulus = random_odd(&mut rng, modulus_limbs);
+            let base = random(&mut rng, modulus_limbs + 1);
+            let exp = random(&mut rng, exp_limbs);
+            let expected = reference_pow(&base, &exp, &modulus);
+            assert_eq!(mod_pow(&base, &exp, &modulus), expected);
+            assert_eq!(mod_pow_ct(&base, &exp, &modulus), Some(expected.clone()));
+            let ctx = MontgomeryContext::new(&modulus).unwrap();
+            assert_eq!(ctx.pow_ct(&base, &exp), expected);
+        }
+    }
+}
+
+#[test]
+fn constant_time_pow_edge_exponents() {
+    let p = Parameters::ffdhe2048().p().clone();
+    let base = BigUint::from_u64(5);
+    assert_eq!(mod_pow_ct(&base, &BigUint::zero(), &p), Some(BigUint::one()));
+    assert_eq!(mod_pow_ct(&base, &BigUint::one(), &p), Some(base.clone()));
+    // exponents wider than the modulus widen the ladder rather than being truncated
+    let wide = &(&p * &p) + &BigUint::from_u64(7);
+    assert_eq!(mod_pow_ct(&base, &wide, &p), Some(reference_pow(&base, &wide, &p)));
+    let p_minus_one = &p - &BigUint::one();
+    assert_eq!(mod_pow_ct(&base, &p_minus_one, &p), Some(BigUint::one()));
+}
+
+#[test]
+fn even_moduli_have_no_constant_time_path() {
+    let modulus = BigUint::from_hex("10000000000000000000000000000000000000000000000000000000000000002").unwrap();
+    assert_eq!(mod_pow_ct(&BigUint::from_u64(3), &BigUint::from_u64(65537), &modulus), None);
+    assert_eq!(mod_pow_ct(&BigUint::from_u64(3), &BigUint::from_u64(5), &BigUint::from_u64(10)), None);
+
+    let params = Parameters::new(modulus, BigUint::from_u64(3));
+    let mut rng = ChaCha20Rng::from_seed([5; 32]);
+    for result in [
+        PrivateKey::new(params.clone(), BigUint::from_u64(12345)),
+        PrivateKey::generate(params.clone(), &mut rng),
+        PrivateKey::generate_with_length(params, 64, &mut rng),
+    ] {
+        let err = result.err().unwrap();
+        assert!(matches!(err.downcast_ref::<DHError>(), Some(DHError::InvalidP)));
+    }
+}
+
+#[test]
+fn montgomery_mul_matches_plain_multiplication() {
+    let mut rng = ChaCha20Rng::from_seed([6; 32]);
+    for limbs in [1, 2, 5, 32] {
+        let modulus = random_odd(&mut rng, limbs);
+        let ctx = MontgomeryContext::new(&modulus).unwrap();
+        // reduced operands, unreduced ones and the edges of the range
+        let operands = [
+            &random(&mut rng, limbs + 2) % &modulus,
+            random(&mut rng, limbs + 2),
+            BigUint::zero(),
+            BigUint::one(),
+            &modulus - &BigUint::one(),
+            modulus.clone(),
+        ];
+        for a in &operands {
+            for b in &operands {
+                assert_eq!(ctx.mul(a, b), &(a * b) % &modulus);
+            }
+        }
+    }
+}
+
+#[test]
+fn pow_mod_p_matches_mod_pow() {
+    let mut rng = ChaCha20Rng::from_seed([7; 32]);
+    let odd = Parameters::ffdhe2048();
+    // an even p has no Montgomery context and falls back to plain division
+    let even = Parameters::new(odd.p() + &BigUint::one(), BigUint::from_u64(2));
+    for params in [odd, even] {
+        for exp_limbs in [0, 1, 4, 32] {
+            let base = random(&mut rng, 33);
+            let exp = random(&mut rng, exp_limbs);
+            assert_eq!(params.pow_mod_p(&base, &exp), reference_pow(&base, &exp, params.p()));
+        }
+    }
+}
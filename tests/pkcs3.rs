@@ -0,0 +1,171 @@
+//! fixtures in tests/data were written by OpenSSL 3.5:
+//! dhparam_2048.pem by `openssl dhparam 2048` and dhparam_2048.der from it with `-outform DER`,
+//! dhparam_1024_private_length.pem by `openssl genpkey -genparam -algorithm DH` and
+//! ffdhe2048.pem by `openssl genpkey -genparam -algorithm DH -pkeyopt group:ffdhe2048`
+
+mod common;
+
+use diffie_hellman::{mod_pow, BigUint, ChaCha20Rng, DHError, Parameters, PrimalityPolicy, PrivateKey};
+
+use common::{error_of, ScriptedRng};
+
+const DHPARAM_2048_PEM: &str = include_str!("data/dhparam_2048.pem");
+const DHPARAM_2048_DER: &[u8] = include_bytes!("data/dhparam_2048.der");
+const DHPARAM_1024_PEM: &str = include_str!("data/dhparam_1024_private_length.pem");
+const FFDHE2048_PEM: &str = include_str!("data/ffdhe2048.pem");
+
+#[test]
+fn openssl_dhparam_round_trips() {
+    for pem in [DHPARAM_2048_PEM, DHPARAM_1024_PEM, FFDHE2048_PEM] {
+        let params = Parameters::from_pkcs3_pem(pem).unwrap();
+        assert_eq!(params.to_pkcs3_pem(), pem);
+        assert_eq!(Parameters::from_pkcs3_der(&params.to_pkcs3_der()).unwrap(), params);
+    }
+
+    let params = Parameters::from_pkcs3_der(DHPARAM_2048_DER).unwrap();
+    assert_eq!(params.to_pkcs3_der(), DHPARAM_2048_DER);
+    assert_eq!(params, Parameters::from_pkcs3_pem(DHPARAM_2048_PEM).unwrap());
+}
+
+#[test]
+fn openssl_dhparam_contents() {
+    let params = Parameters::from_pkcs3_pem(DHPARAM_2048_PEM).unwrap();
+    assert_eq!(params.p().bits(), 2048);
+    assert_eq!(params.private_value_length(), Some(225));
+    params.is_valid_safe_prime_with(PrimalityPolicy::MillerRabin(2)).unwrap();
+
+    let params = Parameters::from_pkcs3_pem(DHPARAM_1024_PEM).unwrap();
+    assert_eq!(params.p().bits(), 1024);
+    assert_eq!(params.private_value_length(), Some(175));
+    params.is_valid_safe_prime_with(PrimalityPolicy::MillerRabin(2)).unwrap();
+
+    // named groups carry no private value length
+    let params = Parameters::from_pkcs3_pem(FFDHE2048_PEM).unwrap();
+    assert_eq!(params.p(), Parameters::ffdhe2048().p());
+    assert_eq!(params.g(), Parameters::ffdhe2048().g());
+    assert_eq!(params.private_value_length(), None);
+}
+
+#[test]
+fn imported_builtin_groups_equal_the_builtin_parameters() {
+    let imported = Parameters::from_pkcs3_pem(FFDHE2048_PEM).unwrap();
+    assert_eq!(imported, Parameters::ffdhe2048());
+    assert_eq!(imported.q(), Parameters::ffdhe2048().q());
+
+    for group in [Parameters::modp_2048(), Parameters::ffdhe3072(), Parameters::rfc5114_2048_224()] {
+        assert_eq!(Parameters::from_pkcs3_der(&group.to_pkcs3_der()).unwrap(), group);
+    }
+    // other parameters come back without q
+    let imported = Parameters::from_pkcs3_pem(DHPARAM_2048_PEM).unwrap();
+    assert_eq!(imported.q(), None);
+}
+
+#[test]
+fn builtin_and_imported_groups_exchange() {
+    let mut rng = ChaCha20Rng::from_seed([25; 32]);
+    let builtin = PrivateKey::generate(Parameters::ffdhe2048(), &mut rng).unwrap();
+    let imported = PrivateKey::generate(Parameters::from_pkcs3_pem(FFDHE2048_PEM).unwrap(), &mut rng).unwrap();
+    let a = builtin.diffie_hellman(&imported.public_key()).unwrap();
+    let b = imported.diffie_hellman(&builtin.public_key()).unwrap();
+    assert_eq!(a.to_bytes_be(), b.to_bytes_be());
+}
+
+#[test]
+fn imported_fixtures_pass_validation() {
+    Parameters::from_pkcs3_pem(FFDHE2048_PEM).unwrap().is_valid().unwrap();
+
+    // g = 2 only generates the order q subgroup, which needs q to be known
+    let params = Parameters::from_pkcs3_pem(DHPARAM_2048_PEM).unwrap();
+    assert!(matches!(error_of(params.is_valid()), DHError::InvalidG));
+    let q = params.is_valid_safe_prime().unwrap();
+    params.with_subgroup_order(q).is_valid().unwrap();
+}
+
+#[test]
+fn private_value_length_limits_generated_keys() {
+    let params = Parameters::from_pkcs3_pem(DHPARAM_2048_PEM).unwrap();
+    assert_eq!(params.private_value_length(), Some(225));
+    let mut rng = ChaCha20Rng::from_seed([24; 32]);
+    let alice = PrivateKey::generate(params.clone(), &mut rng).unwrap();
+    let bob = PrivateKey::generate(params.clone(), &mut rng).unwrap();
+    assert!(alice.diffie_hellman(&bob.public_key()).unwrap() == bob.diffie_hellman(&alice.public_key()).unwrap());
+
+    // 29 bytes of ones are masked down to 225 bits, the largest exponent the limit allows,
+    // and accepted as is, where a full length draw would have needed 256 bytes
+    let mut ones = ScriptedRng::new([vec![0xff; 29]]);
+    let key = PrivateKey::generate(params.clone(), &mut ones).unwrap();
+    assert_eq!(ones.remaining(), 0);
+    let x = &(&BigUint::one() << 225) - &BigUint::one();
+    assert_eq!(*key.public_key().value(), mod_pow(params.g(), &x, params.p()));
+}
+
+#[test]
+fn exported_pem_matches_openssl() {
+    // byte for byte what OpenSSL writes for the same group, down to the line breaks
+    let pem = Parameters::ffdhe2048().to_pkcs3_pem();
+    assert_eq!(pem, FFDHE2048_PEM);
+    assert!(pem.lines().all(|line| line.len() <= 64));
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    let der = DHPARAM_2048_DER;
+    assert!(matches!(error_of(Parameters::from_pkcs3_der(&der[..der.len() - 1])), DHError::InvalidDer));
+    assert!(matches!(error_of(Parameters::from_pkcs3_der(&[der, &[0]].concat())), DHError::InvalidDer));
+    assert!(matches!(error_of(Parameters::from_pkcs3_der(&[])), DHError::InvalidDer));
+
+    // indefinite length
+    let mut indefinite = der.to_vec();
+    indefinite[1] = 0x80;
+    assert!(matches!(error_of(Parameters::from_pkcs3_der(&indefinite)), DHError::InvalidDer));
+
+    // short length written in long form: SEQUENCE { INTEGER 23, INTEGER 5 }
+    assert!(Parameters::from_pkcs3_der(&[0x30, 0x06, 0x02, 0x01, 23, 0x02, 0x01, 5]).is_ok());
+    assert!(matches!(
+        error_of(Parameters::from_pkcs3_der(&[0x30, 0x81, 0x06, 0x02, 0x01, 23, 0x02, 0x01, 5])),
+        DHError::InvalidDer
+    ));
+    // negative and non-minimal integers
+    assert!(matches!(
+        error_of(Parameters::from_pkcs3_der(&[0x30, 0x06, 0x02, 0x01, 0x97, 0x02, 0x01, 5])),
+        DHError::InvalidDer
+    ));
+    assert!(matches!(
+        error_of(Parameters::from_pkcs3_der(&[0x30, 0x07, 0x02, 0x02, 0, 23, 0x02, 0x01, 5])),
+        DHError::InvalidDer
+    ));
+
+    assert!(matches!(error_of(Parameters::from_pkcs3_pem("no pem here")), DHError::InvalidPem));
+    let corrupted = DHPARAM_2048_PEM.replacen('M', "*", 1);
+    assert!(matches!(error_of(Parameters::from_pkcs3_pem(&corrupted)), DHError::InvalidPem));
+    let wrong_label = DHPARAM_2048_PEM.replace("DH PARAMETERS", "X9.42 DH PARAMETERS");
+    assert!(matches!(error_of(Parameters::from_pkcs3_pem(&wrong_label)), DHError::InvalidPem));
+}
+
+#[test]
+fn out_of_range_private_value_length_is_rejected() {
+    let group = Parameters::ffdhe2048();
+    for bits in [0, 2048, 4096, 1 << 62] {
+        let der = group.clone().with_private_value_length(bits).to_pkcs3_der();
+        assert!(matches!(error_of(Parameters::from_pkcs3_der(&der)), DHError::InvalidPrivateValueLength));
+    }
+    let der = group.clone().with_private_value_length(2047).to_pkcs3_der();
+    assert_eq!(Parameters::from_pkcs3_der(&der).unwrap().private_value_length(), Some(2047));
+
+    // lengths past the group order are clamped to it rather than allocated
+    let mut rng = ChaCha20Rng::from_seed([24; 32]);
+    let key = PrivateKey::generate(group.with_private_value_length(1 << 62), &mut rng).unwrap();
+    assert!(key.public_key().validate().is_ok());
+}
+
+#[test]
+fn degenerate_prime_and_base_are_rejected() {
+    let der = |p: u64, g: u64| Parameters::new(BigUint::from_u64(p), BigUint::from_u64(g)).to_pkcs3_der();
+    for p in [0, 1, 2, 24] {
+        assert!(matches!(error_of(Parameters::from_pkcs3_der(&der(p, 2))), DHError::InvalidP));
+    }
+    for g in [0, 1, 22, 23, 30] {
+        assert!(matches!(error_of(Parameters::from_pkcs3_der(&der(23, g))), DHError::InvalidG));
+    }
+    assert!(Parameters::from_pkcs3_der(&der(23, 5)).is_ok());
+}
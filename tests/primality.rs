@@ -0,0 +1,166 @@
+use diffie_hellman::{
+    baillie_psw, jacobi, miller_rabin, miller_rabin_u64, mod_pow, prime_factors, BigUint, Parameters, PrimalityPolicy,
+};
+
+#[test]
+fn strong_pseudoprimes_to_small_bases_are_rejected() {
+    // 3215031751 = 151 * 751 * 28351 passes bases 2, 3, 5 and 7
+    // 4759123141 = 48781 * 97561 passes 2, 7 and 61, the witness set below 2^32
+    // 3825123056546413051 passes every prime base up to 31
+    for n in [3215031751, 4759123141, 3825123056546413051] {
+        assert!(!miller_rabin_u64(n), "{n}");
+        assert!(!miller_rabin(&BigUint::from_u64(n), 1), "{n}");
+        assert!(Parameters::is_prime(&BigUint::from_u64(n)).is_err(), "{n}");
+    }
+}
+
+#[test]
+fn witness_sets_agree_with_trial_division_near_2_32() {
+    let is_prime = |n: u64| n >= 2 && (2..).take_while(|i| i * i <= n).all(|i| !n.is_multiple_of(i));
+    let boundary = 1u64 << 32;
+    for n in boundary - 200..boundary + 200 {
+        assert_eq!(miller_rabin_u64(n), is_prime(n), "{n}");
+    }
+    assert!(miller_rabin_u64(4294967291));
+    assert!(!miller_rabin_u64(4294967297));
+    assert!(miller_rabin_u64(4294967311));
+}
+
+#[test]
+fn primes_around_2_64() {
+    let below = [18446744073709551557, 18446744073709551533];
+    for n in below {
+        assert!(miller_rabin_u64(n), "{n}");
+        assert!(baillie_psw(&BigUint::from_u64(n)), "{n}");
+    }
+    assert!(!miller_rabin_u64(u64::MAX));
+    assert!(!miller_rabin_u64(18446744073709551559));
+
+    // 2^64 + 13 is the first prime past 64 bits and takes the multi-limb path
+    let two_64 = &BigUint::one() << 64;
+    let above = &two_64 + &BigUint::from_u64(13);
+    assert!(miller_rabin(&above, 16));
+    assert!(baillie_psw(&above));
+    for offset in [1, 3, 5, 7, 9, 11] {
+        let n = &two_64 + &BigUint::from_u64(offset);
+        assert!(!miller_rabin(&n, 16), "2^64 + {offset}");
+        assert!(!baillie_psw(&n), "2^64 + {offset}");
+    }
+}
+
+#[test]
+fn default_policy_is_baillie_psw() {
+    assert_eq!(PrimalityPolicy::default(), PrimalityPolicy::BailliePsw);
+}
+
+fn mersenne(exp: usize) -> BigUint {
+    &(&BigUint::one() << exp) - &BigUint::one()
+}
+
+#[test]
+fn baillie_psw_rejects_base_2_strong_pseudoprimes_past_64_bits() {
+    // every composite 2^p - 1 with p prime is a strong pseudoprime to base 2, and these
+    // have no factor small enough for trial division, so only the Lucas test can reject them
+    for exp in [67, 71, 101, 103, 109] {
+        let n = mersenne(exp);
+        assert!(miller_rabin(&n, 1), "2^{exp} - 1");
+        assert!(!baillie_psw(&n), "2^{exp} - 1");
+        assert!(Parameters::is_prime(&n).is_err(), "2^{exp} - 1");
+    }
+}
+
+#[test]
+fn baillie_psw_accepts_large_primes() {
+    for exp in [89, 107, 127, 521, 607] {
+        assert!(baillie_psw(&mersenne(exp)), "2^{exp} - 1");
+    }
+    let group = Parameters::ffdhe2048();
+    let q = group.p() >> 1;
+    assert!(baillie_psw(group.p()));
+    assert!(baillie_psw(&q));
+    assert!(!baillie_psw(&(group.p() + &BigUint::from_u64(2))));
+}
+
+#[test]
+fn baillie_psw_rejects_prime_squares_past_64_bits() {
+    // 4294967311 is the first prime past 2^32, so its square takes the multi-limb path
+    let p = BigUint::from_u64(4294967311);
+    assert!(!baillie_psw(&(&p * &p)));
+    let m = mersenne(89);
+    assert!(!baillie_psw(&(&m * &m)));
+    assert!(!baillie_psw(&(&m * &mersenne(107))));
+}
+
+#[test]
+fn jacobi_matches_known_values() {
+    let small = [(1001, 9907, -1), (19, 45, 1), (8, 21, -1), (5, 21, 1), (6, 15, 0), (0, 1, 1), (0, 3, 0), (30, 7, 1)];
+    for (a, n, expected) in small {
+        assert_eq!(jacobi(&BigUint::from_u64(a), &BigUint::from_u64(n)), expected, "({a}/{n})");
+    }
+
+    let large = [
+        (
+            "c27db4ecf72c2c26786295229623d7cfa9ae7a34254499c7001d9a88096d3737",
+            "42c320a4737c2b3abe14a03569d26b949692e5dfe8cb1855ff",
+            -1,
+        ),
+        (
+            "8a0c510089ce5ef7e91b4ad169fc5360df5ca32ebad5ccc232b7228fcd4a5557",
+            "7d45cf8aa4059a91e1c527e27951c342505f877031bc1e3ac1",
+            -1,
+        ),
+        (
+            "d822e2f9168e5087af895f5b9c2c0ac2cda95957a9b3d1a243f9300cba98666a",
+            "ceb313fc7e8db9b92c903c2ac9316774fe181e290aae9af169",
+            1,
+        ),
+        (
+            "b3642b1932793637c16cf5c51801fd9ab31a5bf371f970cf401fe4fcce06294d",
+            "68ccdf540b5cb53ec017d7ab26fd80206055e8b3eb6cb9185f",
+            0,
+        ),
+    ];
+    for (a, n, expected) in large {
+        let (a, n) = (BigUint::from_hex(a).unwrap(), BigUint::from_hex(n).unwrap());
+        assert_eq!(jacobi(&a, &n), expected);
+    }
+
+    // for a prime modulus the symbol is Euler's criterion a^((p - 1) / 2)
+    let p = mersenne(127);
+    let half = &p >> 1;
+    for a in 2..200u64 {
+        let a = BigUint::from_u64(a);
+        let expected = match mod_pow(&a, &half, &p).is_one() {
+            true => 1,
+            false => -1,
+        };
+        assert_eq!(jacobi(&a, &p), expected);
+    }
+    // a square modulus never gives -1, which is why the Lucas test rules squares out first
+    let square = &p * &p;
+    assert!((2..200u64).all(|a| jacobi(&BigUint::from_u64(a), &square) == 1));
+}
+
+#[test]
+fn prime_factors_splits_composites_past_64_bits() {
+    // 4099 * 1000003^2 * 16777259 * 268435399 * 1000000007, none found by trial division
+    let n = BigUint::from_hex("3640117a6be3891cd159b9f894539899f9").unwrap();
+    let expected: Vec<BigUint> = [4099, 1000003, 16777259, 268435399, 1000000007].map(BigUint::from_u64).to_vec();
+    assert_eq!(prime_factors(&n), Some(expected));
+
+    // trial division alone, and a prime past 64 bits is its own only factor
+    let smooth = BigUint::from_u64(2 * 2 * 3 * 3671);
+    assert_eq!(prime_factors(&smooth), Some([2, 3, 3671].map(BigUint::from_u64).to_vec()));
+    let m = mersenne(127);
+    assert_eq!(prime_factors(&m), Some(vec![m.clone()]));
+}
+
+#[test]
+fn zero_rounds_still_test_the_number() {
+    // 1000000007 * 998244353 * 4294967291, past 64 bits and free of small factors
+    let n = BigUint::from_hex("dda79f496d6683eb37a0ddd").unwrap();
+    assert!(!miller_rabin(&n, 0));
+    assert!(Parameters::is_prime_with_rounds(&n, 0).is_err());
+    assert!(!PrimalityPolicy::MillerRabin(0).is_probable_prime(&n));
+    assert!(miller_rabin(&mersenne(127), 0));
+}
@@ -0,0 +1,37 @@
+use std::collections::HashSet;
+
+use diffie_hellman::{BigUint, Parameters};
+
+/// g is a primitive root of p when its powers g^1 .. g^(p-1) visit every nonzero residue
+fn brute_force_orbit(p: u64, g: u64) -> bool {
+    let mut orbit = HashSet::new();
+    let mut value = 1;
+    for _ in 1..p {
+        value = value * g % p;
+        orbit.insert(value);
+    }
+    orbit.len() as u64 == p - 1 && !orbit.contains(&0)
+}
+
+#[test]
+fn primitive_root_matches_brute_force_orbit() {
+    let primes = (2u64..400).filter(|n| (2..*n).take_while(|i| i * i <= *n).all(|i| n % i != 0));
+    for p in primes {
+        for g in 0..p + 3 {
+            let expected = brute_force_orbit(p, g % p);
+            let actual = Parameters::is_primitive_root(&BigUint::from_u64(p), &BigUint::from_u64(g)).is_ok();
+            assert_eq!(actual, expected, "p = {p}, g = {g}");
+        }
+    }
+}
+
+#[test]
+fn primitive_root_of_safe_prime_past_64_bits() {
+    // p = 2q + 1 with q prime, so g is a primitive root exactly when g^2 != 1 and g^q != 1
+    let p = BigUint::from_hex("1000000000000000000000000000030a3").unwrap();
+    let roots = [2u64, 5, 6, 7, 8, 13, 15, 18];
+    for g in 2u64..20 {
+        let actual = Parameters::is_primitive_root(&p, &BigUint::from_u64(g)).is_ok();
+        assert_eq!(actual, roots.contains(&g), "g = {g}");
+    }
+}
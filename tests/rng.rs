@@ -0,0 +1,64 @@
+use std::{error::Error, io, path::Path};
+
+use diffie_hellman::{fill_from_device, DHError, OsRng, Parameters, PrivateKey, Rng};
+
+#[test]
+fn os_rng_fills_buffers() {
+    let mut rng = OsRng;
+    // getrandom only guarantees whole reads up to 256 bytes, a signal can cut a larger one
+    // short and the loop has to resume it
+    let mut a = vec![0u8; 1 << 20];
+    let mut b = vec![0u8; 1 << 20];
+    rng.fill_bytes(&mut a).unwrap();
+    rng.fill_bytes(&mut b).unwrap();
+    assert_ne!(a, b);
+    // every byte value shows up in a megabyte of random output
+    let mut seen = [false; 256];
+    a.iter().for_each(|byte| seen[*byte as usize] = true);
+    assert!(seen.iter().all(|s| *s));
+
+    rng.fill_bytes(&mut []).unwrap();
+    assert_ne!(rng.next_u64().unwrap(), rng.next_u64().unwrap());
+}
+
+#[test]
+fn os_rng_generates_keys() {
+    let params = Parameters::ffdhe2048();
+    let a = PrivateKey::generate(params.clone(), &mut OsRng).unwrap();
+    let b = PrivateKey::generate(params, &mut OsRng).unwrap();
+    assert_ne!(a.public_key(), b.public_key());
+    let ab = a.diffie_hellman(&b.public_key()).unwrap();
+    let ba = b.diffie_hellman(&a.public_key()).unwrap();
+    assert_eq!(ab.to_bytes_be(), ba.to_bytes_be());
+}
+
+#[test]
+fn entropy_errors_expose_the_io_error() {
+    let err: Box<dyn Error> = Box::new(DHError::Entropy(io::Error::new(io::ErrorKind::Interrupted, "no entropy")));
+    assert!(err.to_string().contains("no entropy"));
+    let source = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+    assert_eq!(source.kind(), io::ErrorKind::Interrupted);
+
+    assert!(DHError::InvalidP.source().is_none());
+}
+
+#[test]
+fn urandom_fallback_fills_buffers_and_reports_errors() {
+    let mut a = vec![0u8; 4096];
+    let mut b = vec![0u8; 4096];
+    fill_from_device(Path::new("/dev/urandom"), &mut a).unwrap();
+    fill_from_device(Path::new("/dev/urandom"), &mut b).unwrap();
+    assert_ne!(a, b);
+
+    // a missing device and one that runs dry both surface as entropy errors
+    for (path, kind) in [
+        ("/dev/no-such-random-device", io::ErrorKind::NotFound),
+        ("/dev/null", io::ErrorKind::UnexpectedEof),
+    ] {
+        let err = fill_from_device(Path::new(path), &mut a).unwrap_err();
+        match err.downcast_ref::<DHError>() {
+            Some(DHError::Entropy(io_err)) => assert_eq!(io_err.kind(), kind, "{path}"),
+            other => panic!("{path}: {other:?}"),
+        }
+    }
+}
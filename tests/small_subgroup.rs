@@ -0,0 +1,119 @@
+use diffie_hellman::{mod_pow, BigUint, ChaCha20Rng, DHError, Parameters, PrivateKey, PublicKey, Rng, SharedSecret};
+
+/// p - 1 = 2 * q * 3 * 5 * 7 * ... * 61, with g generating the subgroup of prime order q
+/// the small factors multiply to more than q, so a private key below q is fully determined
+/// by its residues modulo them
+const P: &str = "105f994c09c7787ac0f0145bb715c95b399b";
+const Q: &str = "a8c40a7daaeddf7f";
+const G: &str = "8101d29193f32bba4b163cb2ee71b75b3dd";
+const SMALL_FACTORS: [u64; 17] = [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61];
+
+fn hex(s: &str) -> BigUint {
+    BigUint::from_hex(s).unwrap()
+}
+
+fn parameters() -> Parameters {
+    Parameters::new(hex(P), hex(G))
+}
+
+fn private_exponent() -> BigUint {
+    let mut rng = ChaCha20Rng::from_seed([19; 32]);
+    &BigUint::from_u64(rng.next_u64().unwrap()) % &hex(Q)
+}
+
+/// an element of order exactly r, from raising some t to (p - 1) / r
+fn element_of_order(params: &Parameters, r: u64) -> BigUint {
+    let p = params.p();
+    let cofactor = &(p - &BigUint::one()) / &BigUint::from_u64(r);
+    (2u64..)
+        .map(|t| mod_pow(&BigUint::from_u64(t), &cofactor, p))
+        .find(|h| !h.is_one())
+        .unwrap()
+}
+
+/// x mod m for each (residue, modulus) pair, combined with the Chinese remainder theorem
+fn crt(congruences: &[(u64, u64)]) -> BigUint {
+    let mut x = BigUint::zero();
+    let mut modulus = BigUint::one();
+    for (residue, m) in congruences {
+        // step x by multiples of the combined modulus until it also fits x = residue mod m
+        while x.div_rem_u64(*m).1 != *residue {
+            x = &x + &modulus;
+        }
+        modulus = &modulus * &BigUint::from_u64(*m);
+    }
+    x
+}
+
+/// the attacker sends an element of small order r, sees the resulting shared secret (in
+/// practice through a MAC or ciphertext keyed by it) and searches the r possible values
+/// to learn the victim's private key modulo r
+fn confinement_attack<F>(params: &Parameters, exchange: F) -> Result<BigUint, Box<dyn std::error::Error>>
+where
+    F: Fn(&PublicKey) -> Result<SharedSecret, Box<dyn std::error::Error>>,
+{
+    let p = params.p();
+    let mut congruences = Vec::new();
+    for r in SMALL_FACTORS {
+        let h = element_of_order(params, r);
+        let secret = exchange(&PublicKey::new(params.clone(), h.clone()))?;
+        let residue = (0..r)
+            .find(|k| mod_pow(&h, &BigUint::from_u64(*k), p) == *secret.value())
+            .unwrap();
+        congruences.push((residue, r));
+    }
+    Ok(crt(&congruences))
+}
+
+#[test]
+fn attack_recovers_private_key_without_subgroup_validation() {
+    // without q the victim can only check that the peer's value lies in [2, p - 2]
+    let params = parameters();
+    let x = private_exponent();
+    let victim = PrivateKey::new(params.clone(), x.clone()).unwrap();
+
+    let recovered = confinement_attack(&params, |peer| victim.diffie_hellman(peer)).unwrap();
+    assert_eq!(recovered, x);
+}
+
+#[test]
+fn subgroup_validation_stops_the_attack() {
+    let params = parameters().with_subgroup_order(hex(Q));
+    let victim = PrivateKey::new(params.clone(), private_exponent()).unwrap();
+
+    let err = confinement_attack(&params, |peer| victim.diffie_hellman(peer)).unwrap_err();
+    assert!(matches!(err.downcast_ref::<DHError>(), Some(DHError::PublicKeyNotInSubgroup)));
+
+    // an honest peer is unaffected
+    let peer = PrivateKey::new(params, BigUint::from_u64(12345)).unwrap();
+    assert!(victim.diffie_hellman(&peer.public_key()).is_ok());
+}
+
+#[test]
+fn minimum_order_check_stops_the_attack_without_q() {
+    // the victim knows nothing about the subgroup structure, only that honest values have
+    // an order of at least 64 bits
+    let params = parameters();
+    let victim = PrivateKey::new(params.clone(), private_exponent()).unwrap();
+
+    let err = confinement_attack(&params, |peer| victim.diffie_hellman_with_min_order(peer, 64)).unwrap_err();
+    assert!(matches!(err.downcast_ref::<DHError>(), Some(DHError::PublicKeyLowOrder)));
+
+    let peer = PrivateKey::new(params, BigUint::from_u64(12345)).unwrap();
+    let guarded = victim.diffie_hellman_with_min_order(&peer.public_key(), 64).unwrap();
+    let plain = victim.diffie_hellman(&peer.public_key()).unwrap();
+    assert_eq!(guarded.value(), plain.value());
+}
+
+#[test]
+fn low_order_elements_are_flagged() {
+    let params = parameters();
+    for r in SMALL_FACTORS {
+        let h = PublicKey::new(params.clone(), element_of_order(&params, r));
+        assert_eq!(h.order().unwrap(), BigUint::from_u64(r));
+        assert!(h.is_low_order(64).unwrap());
+    }
+    let honest = PrivateKey::new(params, BigUint::from_u64(12345)).unwrap().public_key();
+    assert_eq!(honest.order().unwrap(), hex(Q));
+    assert!(!honest.is_low_order(64).unwrap());
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::error_of;
+use diffie_hellman::{BigUint, DHError, Parameters, PrivateKey, PublicKey};
+
+/// p = 23 = 2 * 11 + 1, 5 is a primitive root and 2 generates the subgroup of order 11
+fn full_group() -> Parameters {
+    Parameters::new(BigUint::from_u64(23), BigUint::from_u64(5))
+}
+
+fn subgroup() -> Parameters {
+    Parameters::new(BigUint::from_u64(23), BigUint::from_u64(2)).with_subgroup_order(BigUint::from_u64(11))
+}
+
+#[test]
+fn public_values_outside_2_to_p_minus_2_are_rejected() {
+    for params in [full_group(), subgroup()] {
+        for y in [0, 1, 22, 23, 24] {
+            let peer = PublicKey::new(params.clone(), BigUint::from_u64(y));
+            assert!(matches!(error_of(peer.validate()), DHError::PublicKeyOutOfRange), "y = {y}");
+        }
+        let victim = PrivateKey::new(params.clone(), BigUint::from_u64(3)).unwrap();
+        for y in [0, 1, 22] {
+            let peer = PublicKey::new(params.clone(), BigUint::from_u64(y));
+            assert!(matches!(error_of(victim.diffie_hellman(&peer)), DHError::PublicKeyOutOfRange), "y = {y}");
+        }
+    }
+    // both ends of the valid range are accepted without a subgroup order
+    for y in [2, 21] {
+        PublicKey::new(full_group(), BigUint::from_u64(y)).validate().unwrap();
+    }
+}
+
+#[test]
+fn public_values_outside_the_subgroup_are_rejected() {
+    // 5 has order 22, outside the order 11 subgroup
+    let peer = PublicKey::new(subgroup(), BigUint::from_u64(5));
+    assert!(matches!(error_of(peer.validate()), DHError::PublicKeyNotInSubgroup));
+    // every subgroup element but 1 lies in [2, p - 2]
+    let members = (2..22).filter(|y| PublicKey::new(subgroup(), BigUint::from_u64(*y)).validate().is_ok());
+    assert_eq!(members.count(), 10);
+}
+
+#[test]
+fn private_keys_outside_1_to_order_minus_1_are_rejected() {
+    // the order is q = 11 when known and p - 1 = 22 otherwise
+    for (params, order) in [(subgroup(), 11), (full_group(), 22)] {
+        for x in [0, order, order + 1, 1000] {
+            let result = PrivateKey::new(params.clone(), BigUint::from_u64(x));
+            assert!(matches!(error_of(result), DHError::InvalidPrivateKey), "x = {x}");
+        }
+        for x in [1, order - 1] {
+            PrivateKey::new(params.clone(), BigUint::from_u64(x)).unwrap();
+        }
+    }
+}
+
+#[test]
+fn exchanges_across_different_parameters_are_rejected() {
+    let ours = PrivateKey::new(subgroup(), BigUint::from_u64(3)).unwrap();
+    let other_group = PrivateKey::new(full_group(), BigUint::from_u64(3)).unwrap();
+    assert!(matches!(error_of(ours.diffie_hellman(&other_group.public_key())), DHError::ParameterMismatch));
+
+    // the same p and g without the subgroup order are still a different group
+    let without_q = Parameters::new(BigUint::from_u64(23), BigUint::from_u64(2));
+    let peer = PublicKey::new(without_q, ours.public_key().value().clone());
+    assert!(matches!(error_of(ours.diffie_hellman(&peer)), DHError::ParameterMismatch));
+
+    let ffdhe = PrivateKey::new(Parameters::ffdhe2048(), BigUint::from_u64(3)).unwrap();
+    let peer = PrivateKey::new(Parameters::ffdhe3072(), BigUint::from_u64(5)).unwrap();
+    assert!(matches!(error_of(ffdhe.diffie_hellman(&peer.public_key())), DHError::ParameterMismatch));
+}
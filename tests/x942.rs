@@ -0,0 +1,123 @@
+//! fixtures in tests/data were written by OpenSSL 3.5:
+//! x942_2048_224.pem by `openssl genpkey -genparam -algorithm DHX` with a 2048 bit prime and
+//! 224 bit subgroup, x942_2048_224.der from it with `openssl asn1parse -out`, and
+//! x942_rfc5114_2048_224.pem by the same command with `-pkeyopt dh_rfc5114:2`
+
+mod common;
+
+use diffie_hellman::{BigUint, DHError, Parameters, PrimalityPolicy, PrivateKey, PublicKey, ValidationParams};
+
+use common::error_of;
+
+const X942_2048_224_PEM: &str = include_str!("data/x942_2048_224.pem");
+const X942_2048_224_DER: &[u8] = include_bytes!("data/x942_2048_224.der");
+const RFC5114_2048_224_PEM: &str = include_str!("data/x942_rfc5114_2048_224.pem");
+
+#[test]
+fn openssl_domain_parameters_round_trip() {
+    for pem in [X942_2048_224_PEM, RFC5114_2048_224_PEM] {
+        let params = Parameters::from_x942_pem(pem).unwrap();
+        assert_eq!(params.to_x942_pem().unwrap(), pem);
+    }
+
+    let params = Parameters::from_x942_der(X942_2048_224_DER).unwrap();
+    assert_eq!(params.to_x942_der().unwrap(), X942_2048_224_DER);
+    assert_eq!(params, Parameters::from_x942_pem(X942_2048_224_PEM).unwrap());
+}
+
+#[test]
+fn openssl_domain_parameters_contents() {
+    let params = Parameters::from_x942_pem(X942_2048_224_PEM).unwrap();
+    assert_eq!(params.p().bits(), 2048);
+    assert_eq!(params.q().unwrap().bits(), 224);
+    assert_eq!(params.j(), None);
+    let validation = params.validation_params().unwrap();
+    assert_eq!(validation.seed().len(), 28);
+    assert_eq!(validation.pgen_counter(), 387);
+    params.is_valid_with(PrimalityPolicy::MillerRabin(2)).unwrap();
+
+    // the RFC 5114 group as OpenSSL encodes it, without validation parameters
+    let params = Parameters::from_x942_pem(RFC5114_2048_224_PEM).unwrap();
+    assert_eq!(params, Parameters::rfc5114_2048_224());
+    assert_eq!(params.validation_params(), None);
+}
+
+#[test]
+fn imported_q_enables_subgroup_validation() {
+    let params = Parameters::from_x942_pem(X942_2048_224_PEM).unwrap();
+    let key = PrivateKey::new(params.clone(), BigUint::from_u64(12345)).unwrap();
+
+    // 2 lies outside the order q subgroup of this group
+    let outside = PublicKey::new(params, BigUint::from_u64(2));
+    assert!(matches!(error_of(key.diffie_hellman(&outside)), DHError::PublicKeyNotInSubgroup));
+}
+
+#[test]
+fn cofactor_round_trips_and_is_checked() {
+    let group = Parameters::rfc5114_2048_224();
+    let j = &(group.p() - &BigUint::one()) / group.q().unwrap();
+    let validation = ValidationParams::new(vec![0xa5; 28], 1);
+    let params = group.clone().with_cofactor(j.clone()).with_validation_params(validation.clone());
+
+    let parsed = Parameters::from_x942_der(&params.to_x942_der().unwrap()).unwrap();
+    assert_eq!(parsed.j(), Some(&j));
+    assert_eq!(parsed.validation_params(), Some(&validation));
+    parsed.is_valid_with(PrimalityPolicy::MillerRabin(2)).unwrap();
+
+    let wrong = group.with_cofactor(&j + &BigUint::one());
+    let parsed = Parameters::from_x942_der(&wrong.to_x942_der().unwrap()).unwrap();
+    assert!(matches!(
+        error_of(parsed.is_valid_with(PrimalityPolicy::MillerRabin(2))),
+        DHError::InvalidCofactor
+    ));
+}
+
+#[test]
+fn parameters_without_q_cannot_be_exported() {
+    let group = Parameters::ffdhe2048();
+    let params = Parameters::new(group.p().clone(), group.g().clone());
+    assert!(matches!(error_of(params.to_x942_der()), DHError::MissingSubgroupOrder));
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    // PKCS#3 has no q
+    let pkcs3 = Parameters::ffdhe2048().to_pkcs3_der();
+    assert!(matches!(error_of(Parameters::from_x942_der(&pkcs3)), DHError::InvalidDer));
+
+    // SEQUENCE { INTEGER 23, INTEGER 2, INTEGER 11, SEQUENCE { BIT STRING with 4 unused bits, INTEGER 1 } }
+    let partial_byte_seed = [
+        0x30, 0x12, 0x02, 0x01, 23, 0x02, 0x01, 2, 0x02, 0x01, 11, 0x30, 0x07, 0x03, 0x02, 0x04, 0xf0, 0x02, 0x01, 1,
+    ];
+    assert!(matches!(error_of(Parameters::from_x942_der(&partial_byte_seed)), DHError::InvalidDer));
+    let mut whole_byte_seed = partial_byte_seed;
+    whole_byte_seed[15] = 0;
+    let params = Parameters::from_x942_der(&whole_byte_seed).unwrap();
+    assert_eq!(params.validation_params(), Some(&ValidationParams::new(vec![0xf0], 1)));
+
+    let trailing = [&whole_byte_seed[..], &[0x02, 0x01, 0]].concat();
+    assert!(matches!(error_of(Parameters::from_x942_der(&trailing)), DHError::InvalidDer));
+
+    let pkcs3_label = X942_2048_224_PEM.replace("X9.42 DH PARAMETERS", "DH PARAMETERS");
+    assert!(matches!(error_of(Parameters::from_x942_pem(&pkcs3_label)), DHError::InvalidPem));
+}
+
+#[test]
+fn degenerate_domain_parameters_are_rejected() {
+    let der = |p: u64, g: u64, q: u64| {
+        Parameters::new(BigUint::from_u64(p), BigUint::from_u64(g))
+            .with_subgroup_order(BigUint::from_u64(q))
+            .to_x942_der()
+            .unwrap()
+    };
+    assert!(Parameters::from_x942_der(&der(23, 2, 11)).is_ok());
+    for p in [0, 1, 2, 24] {
+        assert!(matches!(error_of(Parameters::from_x942_der(&der(p, 2, 11))), DHError::InvalidP));
+    }
+    for g in [0, 1, 22, 23] {
+        assert!(matches!(error_of(Parameters::from_x942_der(&der(23, g, 11))), DHError::InvalidG));
+    }
+    for q in [0, 1, 5, 23] {
+        assert!(matches!(error_of(Parameters::from_x942_der(&der(23, 2, q))), DHError::InvalidQ));
+    }
+}